@@ -0,0 +1,18 @@
+import { ENDPOINT } from "./constants";
+
+// Set by pages exported with `cargo visualize --export html`
+const snapshot = (globalThis as any).__CARGO_VISUALIZE_SNAPSHOT__;
+
+export const isSnapshot = snapshot !== undefined;
+
+export async function fetchGraph(): Promise<any> {
+    if (isSnapshot)
+        return snapshot.graph
+    return fetch(`${ENDPOINT}/graph`).then(res => res.json())
+}
+
+export async function fetchPackage(id: string): Promise<any> {
+    if (isSnapshot)
+        return snapshot.packages[id]
+    return fetch(`${ENDPOINT}/package/${id}`).then(x => x.json())
+}
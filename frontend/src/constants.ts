@@ -3,6 +3,28 @@ export const labelFontFamily = "system-ui"
 export const devDepColor = "orange"
 export const buildDepColor = "skyblue"
 export const normalDepColor = "purple"
+
+// Outline of packages when comparing two revisions with `cargo visualize diff`
+export const diffColors: Record<string, string> = {
+    added: "green",
+    removed: "red",
+    upgraded: "blue",
+    downgraded: "darkorange",
+}
+
+// Outline of packages on the compile-time critical path imported with `--timings`
+export const criticalPathColor = "crimson"
+
+// Badge of packages with RustSec advisories, by the most severe kind
+export const advisoryColors: Record<string, string> = {
+    vulnerability: "red",
+    unsound: "darkorange",
+    unmaintained: "goldenrod",
+    notice: "gray",
+}
+
+// Halo of dependencies that are part of a cycle
+export const cycleColor = "red"
 const params = new URLSearchParams(window.location.search);
 export const port = parseInt(params.get("backend") || "8913");
 export const ENDPOINT = import.meta.env.DEV ? `http://127.0.0.1:${port}` : "";
@@ -4,7 +4,7 @@ import { mergeOptions } from '@antv/g6/lib/utils/style';
 
 import type { DisplayObjectConfig } from '@antv/g';
 import type { BaseEdgeStyleProps, LineStyleProps, PathArray, Quadratic, QuadraticStyleProps } from '@antv/g6';
-import { buildDepColor, devDepColor, normalDepColor } from './constants';
+import { buildDepColor, cycleColor, devDepColor, normalDepColor } from './constants';
 
 export interface DepEdgeStyleProps extends LineStyleProps, QuadraticStyleProps {
 
@@ -44,7 +44,11 @@ export class DepEdge extends BaseEdge {
         const data: any = this.context.graph.getEdgeData(this.id).data
         return {
             ...super.getKeyStyle(attributes),
-            lineWidth: 2,
+            lineWidth: data.diff == "added" ? 4 : 2,
+            opacity: data.diff == "removed" ? 0.3 : 1,
+            halo: data.in_cycle,
+            haloStroke: cycleColor,
+            haloLineWidth: 8,
             lineDash: (
                 data.is_optional_direct ? 1 :
                     (data.is_optional ? 3 : 0)
@@ -1,3 +1,4 @@
+import { isSnapshot } from "./api";
 import { ENDPOINT } from "./constants";
 import { hideElement, showElement } from "./dom";
 import { clearTags, insertBadge, insertRawTag, insertTag } from "./tag";
@@ -16,7 +17,13 @@ function urlMapField(field: string): HTMLElement {
 }
 
 function openFieldMapper(id: string) {
-    return (_value: any, field: string) => {
+    return (value: any, field: string) => {
+        // There is no backend to open files in snapshots, just show the path
+        if (isSnapshot) {
+            let ele = document.createElement('span');
+            ele.innerText = value
+            return ele
+        }
         let ele = document.createElement('a');
         ele.innerText = "Open..."
         ele.href = `javascript:`;
@@ -64,8 +71,10 @@ function handleFeatures(meta: any) {
         handlePlainField(meta, 'features', (features: any) => {
             let ele = document.createElement('div');
             ele.className = 'tags'
+            // Highlight the features the resolver enabled
+            const enabled: string[] = meta.enabled_features ?? []
             for (const f of Object.keys(features)) {
-                insertRawTag(`light is-info`, f, ele)
+                insertRawTag(enabled.includes(f) ? `info` : `light is-info`, f, ele)
             }
             return ele
         })
@@ -74,6 +83,16 @@ function handleFeatures(meta: any) {
     }
 }
 
+function formatBytes(bytes: number): string {
+    const units = ["B", "KiB", "MiB", "GiB"]
+    let i = 0
+    while (bytes >= 1024 && i < units.length - 1) {
+        bytes /= 1024
+        i++
+    }
+    return `${bytes.toFixed(i == 0 ? 0 : 1)} ${units[i]}`
+}
+
 function handleLicense(meta: any, id: string) {
     let tr = document.getElementById(`info-license`)!
     if (meta.license) {
@@ -131,12 +150,88 @@ export function prepare_info_tab(id: string, meta: any, data: any) {
     if (data.is_proc_macro) {
         insertTag("danger", "proc-macro", infoTags)
     }
+    if (data.diff && data.diff != "unchanged") {
+        const status = data.previous_version ? `${data.diff} from ${data.previous_version}` : data.diff
+        insertBadge("dark", "Diff", status, infoTags)
+    }
+    // Only set when a policy was given with --license-policy
+    if (data.license_verdict) {
+        const kind = { allow: "success", review: "warning", deny: "danger" }[data.license_verdict as string] ?? null
+        insertBadge(kind, "License", data.license_verdict, infoTags)
+    }
+    // Only set when an advisory database was given with --advisory-db
+    for (const advisory of data.advisories ?? []) {
+        const kind = advisory.kind == "vulnerability" ? "danger" : "warning"
+        insertBadge(kind, advisory.id, `${advisory.kind}: ${advisory.title}`, infoTags)
+    }
+    // Only set with --registry-versions
+    if (data.registry) {
+        const { latest_compatible, latest, yanked } = data.registry
+        if (yanked) {
+            insertTag("danger", "Yanked", infoTags)
+        }
+        if (latest_compatible && latest_compatible != data.version) {
+            insertBadge("warning", "Compatible", latest_compatible, infoTags)
+        }
+        if (latest && latest != data.version) {
+            insertBadge("warning", "Latest", latest, infoTags)
+        }
+    }
+    // Only set when platforms were given with --platform
+    for (const platform of data.platforms ?? []) {
+        insertBadge("dark", "Platform", platform, infoTags)
+    }
+    if (data.feature) {
+        insertBadge("info", "Feature", data.feature, infoTags)
+    }
     if (meta.rust_version) {
         insertBadge("warning", "Rust", meta.rust_version, infoTags)
     }
     if (meta.edition) {
         insertBadge("success", "Edition", meta.edition, infoTags)
     }
+    // Number of crates that would disappear together with this one
+    if (!data.is_ws_member && data.exclusive_deps + data.shared_deps > 0) {
+        insertBadge("danger", "Exclusive Deps", `${data.exclusive_deps}`, infoTags)
+        insertBadge("link", "Shared Deps", `${data.shared_deps}`, infoTags)
+    }
+    // How load-bearing the package is
+    if (!data.is_ws_member && data.metrics) {
+        const { depth, paths, reverse_deps, betweenness } = data.metrics
+        if (depth != null) {
+            insertBadge("info", "Depth", `${depth}`, infoTags)
+        }
+        insertBadge("info", "Paths", paths.toLocaleString(), infoTags)
+        insertBadge("info", "Reverse Deps", `${reverse_deps}`, infoTags)
+        insertBadge("info", "Betweenness", betweenness.toFixed(4), infoTags)
+    }
+    // Only set when measured with --footprint
+    if (data.footprint) {
+        const { own, exclusive, total } = data.footprint
+        insertBadge("primary", "Lines", `${own.rust_lines.toLocaleString()}`, infoTags)
+        if (!data.is_ws_member) {
+            insertBadge("danger", "Exclusive Lines", `${exclusive.rust_lines.toLocaleString()}`, infoTags)
+        }
+        insertBadge("link", "Total Lines", `${total.rust_lines.toLocaleString()}`, infoTags)
+        insertBadge("white", "Total Size", formatBytes(total.bytes), infoTags)
+    }
+    // Only set when scanned with --scan-unsafe
+    if (data.unsafe_code) {
+        const { own, total, forbids_unsafe } = data.unsafe_code
+        const items = (c: any) => c.blocks + c.functions + c.impls + c.traits
+        insertBadge(items(own) > 0 ? "danger" : "success", "Unsafe", `${items(own)}`, infoTags)
+        insertBadge(items(total) > 0 ? "warning" : "success", "Total Unsafe", `${items(total)}`, infoTags)
+        if (forbids_unsafe) {
+            insertTag("success", "forbid(unsafe_code)", infoTags)
+        }
+    }
+    // Only set when imported with --timings
+    if (data.build_time != null) {
+        insertBadge(data.on_critical_path ? "danger" : "warning", "Build Time", `${data.build_time.toFixed(2)}s`, infoTags)
+    }
+    if (data.on_critical_path) {
+        insertTag("danger", "Critical Path", infoTags)
+    }
     // Metadata
     if (meta.description) {
         infoDescription.innerText = meta.description
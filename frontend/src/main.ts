@@ -5,11 +5,12 @@ import { DepNode } from './dep-node';
 import layouts from './layouts';
 import { DepEdge } from './dep-edge';
 import { labelText } from './pure';
-import { ENDPOINT, labelFontFamily } from './constants';
+import { advisoryColors, criticalPathColor, diffColors, labelFontFamily } from './constants';
+import { fetchGraph, fetchPackage } from './api';
 import { prepare_info_tab } from './info';
 import { graphHeight, graphWidth, initializeGraphResizeHandle } from './resize';
 
-let data = await fetch(`${ENDPOINT}/graph`).then(res => res.json());
+let data = await fetchGraph();
 
 const layoutElement = document.getElementById("layout")! as HTMLSelectElement;
 const resetElement = document.getElementById("reset")! as HTMLSelectElement;
@@ -51,9 +52,23 @@ const graph = new Graph({
         else
           return "white"
       },
-      stroke: "#222",
-      lineWidth: 1,
-      badge: true
+      stroke: (node) => {
+        const data: any = node.data
+        return diffColors[data?.diff] ?? (data?.on_critical_path ? criticalPathColor : "#222")
+      },
+      lineWidth: (node) => {
+        const data: any = node.data
+        return diffColors[data?.diff] || data?.on_critical_path ? 4 : 1
+      },
+      badge: true,
+      // Only set when an advisory database was given with --advisory-db
+      badges: (node) => {
+        const advisories: any[] = (node.data as any)?.advisories ?? []
+        if (advisories.length == 0)
+          return []
+        // Advisories are sorted by severity
+        return [{ text: `${advisories.length}`, placement: "right-top", backgroundFill: advisoryColors[advisories[0].kind] }]
+      },
     },
     state: {
       "selected": {
@@ -108,7 +123,7 @@ graph.on(NodeEvent.CLICK, async (e: Event) => {
   let target = e.target as any;
   let meta = crateCache.get(target.id);
   if (!meta) {
-    meta = await fetch(`${ENDPOINT}/package/${target.id}`).then(x => x.json());
+    meta = await fetchPackage(target.id);
     crateCache.set(target.id, meta)
   }
   let node = graph.getElementData(target.id);
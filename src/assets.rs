@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use axum::{
     http::{header, StatusCode, Uri},
     response::{IntoResponse, Response},
@@ -8,6 +10,11 @@ use rust_embed::Embed;
 #[folder = "frontend/dist"]
 struct Asset;
 
+/// Raw content of an embedded asset, `path` is relative to `frontend/dist`
+pub fn get(path: &str) -> Option<Cow<'static, [u8]>> {
+    Asset::get(path).map(|f| f.data)
+}
+
 pub async fn handler_index() -> impl IntoResponse {
     static_handler("/index.html".parse::<Uri>().unwrap()).await
 }
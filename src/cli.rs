@@ -1,12 +1,32 @@
-use clap::{value_parser, Arg, ArgAction, Command};
+use clap::{builder::PossibleValuesParser, value_parser, Arg, ArgAction, Command};
+
+use crate::export::ExportFormat;
 
 pub(crate) struct Config {
     pub no_open: bool,
     pub bind: Option<String>,
+    pub export: Option<ExportFormat>,
+    pub output: Option<String>,
+    pub why: Option<String>,
+    pub duplicates: bool,
+    pub cycles: bool,
+    pub metrics: bool,
+    pub diff: Option<(String, String)>,
+    pub license_policy: Option<String>,
+    pub check_licenses: bool,
+    pub advisory_db: Option<String>,
+    pub advisories: bool,
+    pub registry_versions: bool,
+    pub outdated: bool,
+    pub footprint: bool,
+    pub scan_unsafe: bool,
+    pub unsafe_report: bool,
+    pub timings: Option<String>,
     pub build_deps: bool,
     pub dev_deps: bool,
     pub target_deps: bool,
     pub dedup_transitive_deps: bool,
+    pub feature_graph: bool,
     pub hide: Vec<String>,
     pub exclude: Vec<String>,
     pub include: Vec<String>,
@@ -19,6 +39,7 @@ pub(crate) struct Config {
     pub all_features: bool,
     pub no_default_features: bool,
     pub filter_platform: Vec<String>,
+    pub platforms: Vec<String>,
     pub manifest_path: Option<String>,
     pub frozen: bool,
     pub locked: bool,
@@ -27,6 +48,15 @@ pub(crate) struct Config {
 }
 
 pub(crate) fn parse_options() -> Config {
+    parse_args(std::env::args_os())
+}
+
+/// Parses the command line `args`, including the program name
+pub(crate) fn parse_args<I, T>(args: I) -> Config
+where
+    I: IntoIterator<Item = T>,
+    T: Into<std::ffi::OsString> + Clone,
+{
     let matches = Command::new("cargo-visualize")
         .bin_name("cargo")
         .version(env!("CARGO_PKG_VERSION"))
@@ -38,6 +68,190 @@ pub(crate) fn parse_options() -> Config {
                 .arg(Arg::new("bind").long("bind").action(ArgAction::Set).help(
                     "The address and port to listen on. (e.g. 127.0.0.1:8913)",
                 ))
+                .arg(
+                    Arg::new("export")
+                        .long("export")
+                        .action(ArgAction::Set)
+                        .value_parser(PossibleValuesParser::new(
+                            ExportFormat::ALL.iter().map(|(name, _)| name),
+                        ))
+                        .value_name("FORMAT")
+                        .help(
+                            "Write the dependency graph in the given format instead of \
+                             starting the web service",
+                        ),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .action(ArgAction::Set)
+                        .requires("export")
+                        .value_name("PATH")
+                        .help(
+                            "File to write the exported graph to, or directory for formats \
+                             consisting of multiple files (csv, tsv). Default: stdout or the \
+                             current directory",
+                        ),
+                )
+                .arg(
+                    Arg::new("why")
+                        .long("why")
+                        .action(ArgAction::Set)
+                        .conflicts_with("export")
+                        .value_name("PACKAGE")
+                        .help(
+                            "Print every dependency chain from the workspace members to the \
+                             given package (name, name@version) instead of starting the web \
+                             service",
+                        ),
+                )
+                .arg(
+                    Arg::new("duplicates")
+                        .long("duplicates")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["export", "why"])
+                        .help(
+                            "Print the packages that occur with multiple versions, along with \
+                             the requirements that pull in each version, instead of starting \
+                             the web service",
+                        ),
+                )
+                .arg(
+                    Arg::new("cycles")
+                        .long("cycles")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["export", "why", "duplicates"])
+                        .help(
+                            "Print the packages that depend on each other in a loop, e.g. \
+                             through dev-dependencies, instead of starting the web service",
+                        ),
+                )
+                .arg(
+                    Arg::new("metrics")
+                        .long("metrics")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["export", "why", "duplicates", "cycles"])
+                        .help(
+                            "Print structural metrics of every package, most load-bearing \
+                             first, instead of starting the web service",
+                        ),
+                )
+                .arg(
+                    Arg::new("license_policy")
+                        .long("license-policy")
+                        .action(ArgAction::Set)
+                        .value_name("FILE")
+                        .help("Evaluate the license of every package against a TOML policy file"),
+                )
+                .arg(
+                    Arg::new("check_licenses")
+                        .long("check-licenses")
+                        .action(ArgAction::SetTrue)
+                        .requires("license_policy")
+                        .conflicts_with_all(["export", "why", "duplicates", "cycles", "metrics"])
+                        .help(
+                            "Print the licenses that need review or are denied by the license \
+                             policy instead of starting the web service, and fail if any \
+                             package is denied",
+                        ),
+                )
+                .arg(
+                    Arg::new("advisory_db")
+                        .long("advisory-db")
+                        .action(ArgAction::Set)
+                        .value_name("DIR")
+                        .help(
+                            "Match every package against a local checkout of the RustSec \
+                             advisory database",
+                        ),
+                )
+                .arg(
+                    Arg::new("advisories")
+                        .long("advisories")
+                        .action(ArgAction::SetTrue)
+                        .requires("advisory_db")
+                        .conflicts_with_all([
+                            "export",
+                            "why",
+                            "duplicates",
+                            "cycles",
+                            "metrics",
+                            "check_licenses",
+                        ])
+                        .help(
+                            "Print the packages that advisories apply to, along with the \
+                             dependency paths that pull them in, instead of starting the web \
+                             service",
+                        ),
+                )
+                .arg(
+                    Arg::new("registry_versions")
+                        .long("registry-versions")
+                        .action(ArgAction::SetTrue)
+                        .help(
+                            "Look up newer and yanked releases of every package in the registry \
+                             index cached by cargo, without network access",
+                        ),
+                )
+                .arg(
+                    Arg::new("outdated")
+                        .long("outdated")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all([
+                            "export",
+                            "why",
+                            "duplicates",
+                            "cycles",
+                            "metrics",
+                            "check_licenses",
+                            "advisories",
+                        ])
+                        .help(
+                            "Print the packages that are yanked or have newer releases in the \
+                             cached registry index instead of starting the web service \
+                             (implies --registry-versions)",
+                        ),
+                )
+                .arg(Arg::new("footprint").long("footprint").action(ArgAction::SetTrue).help(
+                    "Measure the source files and Rust lines of code of every package on disk",
+                ))
+                .arg(
+                    Arg::new("scan_unsafe")
+                        .long("scan-unsafe")
+                        .action(ArgAction::SetTrue)
+                        .help("Count the uses of `unsafe` in the sources of every package"),
+                )
+                .arg(
+                    Arg::new("unsafe_report")
+                        .long("unsafe-report")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all([
+                            "export",
+                            "why",
+                            "duplicates",
+                            "cycles",
+                            "metrics",
+                            "check_licenses",
+                            "advisories",
+                            "outdated",
+                        ])
+                        .help(
+                            "Print the dependencies that use `unsafe`, along with the dependency \
+                             paths that pull them in, instead of starting the web service \
+                             (implies --scan-unsafe)",
+                        ),
+                )
+                .arg(
+                    Arg::new("timings")
+                        .long("timings")
+                        .action(ArgAction::Set)
+                        .value_name("FILE")
+                        .help(
+                            "Show compile times from the HTML report of `cargo build --timings` \
+                             or from cargo's JSON messages, and mark the critical path",
+                        ),
+                )
                 .arg(Arg::new("all_deps").long("all-deps").action(ArgAction::SetTrue).help(
                     "Include all dependencies in the graph \
                      (shorthand for --build-deps --dev-deps --target-deps)",
@@ -69,6 +283,15 @@ pub(crate) fn parse_options() -> Config {
                              least one transitive dependency of the same kind.",
                         ),
                 )
+                .arg(
+                    Arg::new("feature_graph")
+                        .long("feature-graph")
+                        .action(ArgAction::SetTrue)
+                        .help(
+                            "Add a node for every enabled feature, linked to the features and \
+                             optional dependencies it enables",
+                        ),
+                )
                 .arg(
                     Arg::new("hide")
                         .long("hide")
@@ -164,6 +387,18 @@ pub(crate) fn parse_options() -> Config {
                         .number_of_values(1)
                         .value_name("TRIPLE"),
                 )
+                .arg(
+                    Arg::new("platform")
+                        .long("platform")
+                        .help(
+                            "Only include target-specific dependencies that apply to the given \
+                             target-triple, evaluated from a single resolve for all platforms. \
+                             Can be given multiple times, implies --target-deps",
+                        )
+                        .action(ArgAction::Append)
+                        .number_of_values(1)
+                        .value_name("TRIPLE"),
+                )
                 .arg(
                     Arg::new("manifest_path")
                         .long("manifest-path")
@@ -198,20 +433,55 @@ pub(crate) fn parse_options() -> Config {
                         .value_name("FLAG")
                         .action(ArgAction::Append)
                         .number_of_values(1),
+                )
+                .subcommand(
+                    Command::new("diff")
+                        .about(
+                            "Show the changes to the dependency graph between two revisions, \
+                             instead of the graph of the current one",
+                        )
+                        .arg(Arg::new("old").required(true).value_name("OLD").help(
+                            "Git revision, or path to a workspace directory, manifest or lockfile",
+                        ))
+                        .arg(Arg::new("new").required(true).value_name("NEW").help(
+                            "Git revision, or path to a workspace directory, manifest or lockfile",
+                        )),
                 ),
         )
-        .get_matches();
+        .get_matches_from(args);
 
     let matches = matches.subcommand_matches("visualize").unwrap();
 
     let no_open = matches.get_flag("no_open");
     let bind = matches.get_one("bind").cloned();
+    let export = matches.get_one::<String>("export").map(|f| ExportFormat::from_name(f));
+    let output = matches.get_one("output").cloned();
+    let why = matches.get_one("why").cloned();
+    let duplicates = matches.get_flag("duplicates");
+    let cycles = matches.get_flag("cycles");
+    let metrics = matches.get_flag("metrics");
+    let license_policy = matches.get_one("license_policy").cloned();
+    let check_licenses = matches.get_flag("check_licenses");
+    let advisory_db = matches.get_one("advisory_db").cloned();
+    let advisories = matches.get_flag("advisories");
+    let outdated = matches.get_flag("outdated");
+    let registry_versions = outdated || matches.get_flag("registry_versions");
+    let footprint = matches.get_flag("footprint");
+    let unsafe_report = matches.get_flag("unsafe_report");
+    let scan_unsafe = unsafe_report || matches.get_flag("scan_unsafe");
+    let timings = matches.get_one("timings").cloned();
+    let diff = matches.subcommand_matches("diff").map(|m| {
+        let rev = |name| m.get_one::<String>(name).unwrap().clone();
+        (rev("old"), rev("new"))
+    });
 
     let all_deps = matches.get_flag("all_deps");
     let build_deps = all_deps || matches.get_flag("build_deps");
     let dev_deps = all_deps || matches.get_flag("dev_deps");
-    let target_deps = all_deps || matches.get_flag("target_deps");
+    let platforms = matches.get_many("platform").map_or_else(Vec::new, collect_owned);
+    let target_deps = all_deps || matches.get_flag("target_deps") || !platforms.is_empty();
     let dedup_transitive_deps = matches.get_flag("dedup_transitive_deps");
+    let feature_graph = matches.get_flag("feature_graph");
     let hide = matches.get_many("hide").map_or_else(Vec::new, collect_owned);
     let exclude = matches.get_many("exclude").map_or_else(Vec::new, collect_owned);
     let include = matches.get_many("include").map_or_else(Vec::new, collect_owned);
@@ -233,10 +503,28 @@ pub(crate) fn parse_options() -> Config {
     Config {
         no_open,
         bind,
+        export,
+        output,
+        why,
+        duplicates,
+        cycles,
+        metrics,
+        diff,
+        license_policy,
+        check_licenses,
+        advisory_db,
+        advisories,
+        registry_versions,
+        outdated,
+        footprint,
+        scan_unsafe,
+        unsafe_report,
+        timings,
         build_deps,
         dev_deps,
         target_deps,
         dedup_transitive_deps,
+        feature_graph,
         hide,
         exclude,
         include,
@@ -248,6 +536,7 @@ pub(crate) fn parse_options() -> Config {
         all_features,
         no_default_features,
         filter_platform,
+        platforms,
         manifest_path,
         frozen,
         locked,
@@ -4,8 +4,11 @@ use std::{
 };
 
 use cargo_metadata::DependencyKind as MetaDepKind;
+use cargo_platform::Platform;
 use serde::Serialize;
 
+use crate::diff::DiffStatus;
+
 #[derive(Clone, Debug, Default, Serialize)]
 pub(crate) struct DepInfo {
     #[serde(skip)]
@@ -13,6 +16,16 @@ pub(crate) struct DepInfo {
     pub source: String,
     pub target: String,
     pub edge_no: u32,
+    /// Features of the dependency requested by the dependent
+    pub features: Vec<String>,
+    pub uses_default_features: bool,
+    /// Target triple or `cfg()` expression this dependency is specific to
+    pub platform: Option<Platform>,
+    /// How the dependency changed, when comparing two revisions
+    pub diff: Option<DiffStatus>,
+    /// Whether the dependency is part of a cycle, e.g. through a dev-dependency on a package that
+    /// depends on the dependent again
+    pub in_cycle: bool,
     #[serde(flatten)]
     pub inner: DepInfoInner,
 }
@@ -21,8 +34,7 @@ pub(crate) struct DepInfo {
 pub(crate) struct DepInfoInner {
     pub kind: DepKind,
 
-    // TODO: instead collect targets, once we can actually evaluate whether they apply
-    // (would be a really nice feature to show a linux- or windows-specific depgraph)
+    /// whether this dependency is only used on some platforms, see `DepInfo::platform`
     pub is_target_dep: bool,
 
     /// whether this dependency could be removed by deactivating a cargo feature
@@ -97,6 +109,11 @@ impl DepKind {
     pub fn is_dev_only(&self) -> bool {
         self.host != BuildFlag::Always && self.target != BuildFlag::Always
     }
+
+    /// Whether the dependency is only built for the host, like build-dependencies and proc-macros
+    pub fn is_host_only(&self) -> bool {
+        self.host != BuildFlag::Never && self.target == BuildFlag::Never
+    }
 }
 
 impl Default for DepKind {
@@ -0,0 +1,296 @@
+#[cfg(unix)]
+use std::os::unix::fs::symlink as symlink_dir;
+#[cfg(windows)]
+use std::os::windows::fs::symlink_dir;
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{bail, Context};
+use cargo_metadata::{camino::Utf8PathBuf, semver::Version, MetadataCommand};
+use nanoid::nanoid;
+use serde::Serialize;
+
+use crate::{
+    build_graph,
+    cli::Config,
+    dep_info::DepInfo,
+    graph::{DepGraph, DepMap},
+};
+
+/// How a package or dependency changed between two revisions
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum DiffStatus {
+    Added,
+    Removed,
+    Upgraded,
+    Downgraded,
+    Unchanged,
+}
+
+impl DiffStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Removed => "removed",
+            Self::Upgraded => "upgraded",
+            Self::Downgraded => "downgraded",
+            Self::Unchanged => "unchanged",
+        }
+    }
+}
+
+/// Builds the graphs of the `old` and `new` revisions and merges them into one, where every
+/// package and dependency is tagged with how it changed.
+///
+/// Revisions can be git revisions of the current repository, or paths to a workspace directory,
+/// manifest or lockfile. Lockfiles are resolved against the manifests of the current workspace.
+///
+/// The checkouts of both revisions are returned as well. They must be kept alive as long as the
+/// paths of the new revision's workspace are in use.
+pub(crate) fn diff_graphs(
+    config: &Config,
+    old: &str,
+    new: &str,
+) -> anyhow::Result<(DepGraph, DepMap, Utf8PathBuf, [Checkout; 2])> {
+    let old_checkout = Checkout::new(config, old)?;
+    let new_checkout = Checkout::new(config, new)?;
+    let (old_graph, old_depmap, _) =
+        build_graph(config, Some(old_checkout.manifest_path()?), old_checkout.is_locked())?;
+    let (new_graph, new_depmap, workspace_root) =
+        build_graph(config, Some(new_checkout.manifest_path()?), new_checkout.is_locked())?;
+
+    let (graph, depmap) = merge((old_graph, old_depmap), (new_graph, new_depmap));
+    Ok((graph, depmap, workspace_root, [old_checkout, new_checkout]))
+}
+
+fn merge(old: (DepGraph, DepMap), new: (DepGraph, DepMap)) -> (DepGraph, DepMap) {
+    let (old_graph, old_depmap) = old;
+    let (mut graph, mut depmap) = new;
+
+    let mut old_versions = HashMap::<&str, Vec<&Version>>::new();
+    for pkg in old_graph.node_weights() {
+        old_versions.entry(&pkg.name).or_default().push(&pkg.version);
+    }
+    let mut new_versions = HashMap::<String, Vec<Version>>::new();
+    for pkg in graph.node_weights() {
+        new_versions.entry(pkg.name.clone()).or_default().push(pkg.version.clone());
+    }
+
+    // Pair up versions that were replaced, oldest with oldest. The remaining ones were really
+    // added or removed.
+    let mut replaced = HashMap::<String, Version>::new();
+    let mut old_ids = HashMap::<String, String>::new();
+    for (name, new) in &new_versions {
+        let Some(old) = old_versions.get(name.as_str()) else {
+            continue;
+        };
+        let mut removed: Vec<_> = old.iter().filter(|v| !new.contains(v)).collect();
+        let mut added: Vec<_> = new.iter().filter(|v| !old.contains(v)).collect();
+        removed.sort();
+        added.sort();
+        for (&&previous, version) in removed.iter().zip(added) {
+            let id = format!("{name} {version}");
+            old_ids.insert(format!("{name} {previous}"), id.clone());
+            replaced.insert(id, previous.clone());
+        }
+    }
+
+    let old_package_ids: HashSet<_> = old_graph.node_weights().map(|pkg| &pkg.id).collect();
+    for pkg in graph.node_weights_mut() {
+        if let Some(previous) = replaced.get(&pkg.id) {
+            pkg.diff = Some(if pkg.version > *previous {
+                DiffStatus::Upgraded
+            } else {
+                DiffStatus::Downgraded
+            });
+            pkg.previous_version = Some(previous.clone());
+        } else if old_package_ids.contains(&pkg.id) {
+            pkg.diff = Some(DiffStatus::Unchanged);
+        } else {
+            pkg.diff = Some(DiffStatus::Added);
+        }
+    }
+
+    let mut indices: HashMap<_, _> =
+        graph.node_indices().map(|idx| (graph[idx].id.clone(), idx)).collect();
+    for pkg in old_graph.node_weights() {
+        if indices.contains_key(&pkg.id) || old_ids.contains_key(&pkg.id) {
+            continue;
+        }
+        let mut pkg = pkg.clone();
+        pkg.diff = Some(DiffStatus::Removed);
+        if let Some(meta) = old_depmap.get(&pkg.id) {
+            depmap.insert(pkg.id.clone(), meta.clone());
+        }
+        let id = pkg.id.clone();
+        indices.insert(id, graph.add_node(pkg));
+    }
+
+    // Edges are identified by the packages they connect and their kind, after accounting for
+    // replaced versions
+    let merged_id = |id: &String| old_ids.get(id).unwrap_or(id).clone();
+    let old_edges: HashSet<_> = old_graph
+        .edge_weights()
+        .map(|dep| edge_key(dep, merged_id(&dep.source), merged_id(&dep.target)))
+        .collect();
+    let mut new_edges = HashSet::new();
+    for dep in graph.edge_weights_mut() {
+        let key = edge_key(dep, dep.source.clone(), dep.target.clone());
+        dep.diff =
+            Some(if old_edges.contains(&key) { DiffStatus::Unchanged } else { DiffStatus::Added });
+        new_edges.insert(key);
+    }
+    for dep in old_graph.edge_weights() {
+        let (source, target) = (merged_id(&dep.source), merged_id(&dep.target));
+        if new_edges.contains(&edge_key(dep, source.clone(), target.clone())) {
+            continue;
+        }
+        let (source_idx, target_idx) = (indices[&source], indices[&target]);
+        let weight = DepInfo {
+            id: nanoid!(),
+            edge_no: graph.edges_connecting(source_idx, target_idx).count() as u32,
+            diff: Some(DiffStatus::Removed),
+            source,
+            target,
+            ..dep.clone()
+        };
+        graph.add_edge(source_idx, target_idx, weight);
+    }
+
+    (graph, depmap)
+}
+
+fn edge_key(dep: &DepInfo, source: String, target: String) -> (String, String, bool, bool, bool) {
+    (source, target, dep.inner.is_normal, dep.inner.is_build, dep.inner.is_dev)
+}
+
+/// A revision that the graph can be built for
+pub(crate) enum Checkout {
+    Path(PathBuf),
+    /// Temporary git worktree, removed again when dropped
+    Worktree {
+        repo: PathBuf,
+        dir: PathBuf,
+        manifest_path: PathBuf,
+    },
+    /// Temporary copy of the current workspace with another lockfile, removed again when dropped
+    Lockfile {
+        dir: PathBuf,
+        manifest_path: PathBuf,
+    },
+}
+
+impl Checkout {
+    fn new(config: &Config, rev: &str) -> anyhow::Result<Self> {
+        let path = Path::new(rev);
+        if path.is_dir() {
+            return Ok(Self::Path(path.join("Cargo.toml")));
+        } else if path.is_file() && path.extension().is_some_and(|ext| ext == "lock") {
+            return Self::with_lockfile(config, path);
+        } else if path.is_file() {
+            return Ok(Self::Path(path.to_owned()));
+        }
+
+        let manifest_path = current_manifest(config)?;
+        let manifest_dir = manifest_path.parent().unwrap();
+        let repo = PathBuf::from(git(manifest_dir, &["rev-parse", "--show-toplevel"])?);
+        git(&repo, &["rev-parse", "--verify", "--quiet", &format!("{rev}^{{commit}}")])
+            .with_context(|| format!("`{rev}` is neither a path nor a git revision"))?;
+
+        let dir = env::temp_dir().join(format!("cargo-visualize-{}", nanoid!(10)));
+        git(&repo, &["worktree", "add", "--detach", "--quiet", path_str(&dir)?, rev])?;
+        let manifest_path = dir.join(manifest_path.strip_prefix(&repo)?);
+        Ok(Self::Worktree { repo, dir, manifest_path })
+    }
+
+    /// Sets up the current workspace with `lockfile`. Directories are linked rather than copied,
+    /// only the files in the workspace root are copied so that the lockfile next to the root
+    /// manifest can be replaced.
+    fn with_lockfile(config: &Config, lockfile: &Path) -> anyhow::Result<Self> {
+        let manifest_path = current_manifest(config)?;
+        let metadata = MetadataCommand::new().manifest_path(&manifest_path).no_deps().exec()?;
+        let root = metadata.workspace_root.as_std_path();
+        let target_dir = metadata.target_directory.as_std_path();
+
+        let dir = env::temp_dir().join(format!("cargo-visualize-{}", nanoid!(10)));
+        fs::create_dir(&dir)?;
+        // Created before filling the directory so that it is removed on errors as well
+        let checkout = Self::Lockfile {
+            manifest_path: dir.join(manifest_path.strip_prefix(root)?),
+            dir: dir.clone(),
+        };
+        for entry in fs::read_dir(root)? {
+            let entry = entry?;
+            let (name, path) = (entry.file_name(), entry.path());
+            if name == "Cargo.lock" || name == ".git" || path == target_dir {
+                continue;
+            }
+            if entry.file_type()?.is_dir() {
+                symlink_dir(&path, dir.join(&name))?;
+            } else {
+                fs::copy(&path, dir.join(&name))?;
+            }
+        }
+        fs::copy(lockfile, dir.join("Cargo.lock"))
+            .with_context(|| format!("Failed to copy {}", lockfile.display()))?;
+        Ok(checkout)
+    }
+
+    fn manifest_path(&self) -> anyhow::Result<&str> {
+        match self {
+            Self::Path(path)
+            | Self::Worktree { manifest_path: path, .. }
+            | Self::Lockfile { manifest_path: path, .. } => path_str(path),
+        }
+    }
+
+    /// Whether cargo must use the lockfile as is, which is the point of comparing lockfiles
+    fn is_locked(&self) -> bool {
+        matches!(self, Self::Lockfile { .. })
+    }
+}
+
+impl Drop for Checkout {
+    fn drop(&mut self) {
+        match self {
+            Self::Path(_) => {}
+            Self::Worktree { repo, dir, .. } => {
+                if let Err(e) =
+                    path_str(dir).and_then(|dir| git(repo, &["worktree", "remove", "--force", dir]))
+                {
+                    eprintln!("Failed to remove git worktree {}: {e:#}", dir.display());
+                }
+            }
+            // Only removes the links, not the directories of the workspace
+            Self::Lockfile { dir, .. } => {
+                if let Err(e) = fs::remove_dir_all(&*dir) {
+                    eprintln!("Failed to remove {}: {e}", dir.display());
+                }
+            }
+        }
+    }
+}
+
+fn current_manifest(config: &Config) -> anyhow::Result<PathBuf> {
+    Path::new(config.manifest_path.as_deref().unwrap_or("Cargo.toml"))
+        .canonicalize()
+        .context("Failed to find the manifest of the current workspace")
+}
+
+fn git(dir: &Path, args: &[&str]) -> anyhow::Result<String> {
+    let output =
+        Command::new("git").arg("-C").arg(dir).args(args).output().context("Failed to run git")?;
+    if !output.status.success() {
+        bail!("git {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+fn path_str(path: &Path) -> anyhow::Result<&str> {
+    path.to_str().with_context(|| format!("{} is not valid UTF-8", path.display()))
+}
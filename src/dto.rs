@@ -1,6 +1,25 @@
 use serde::Serialize;
 
-use crate::{dep_info::DepInfo, package::Package};
+use crate::{dep_info::DepInfo, footprint::PackageFootprint, graph::DepGraph, package::Package};
+
+/// Metadata of a package, along with what the resolver decided about it
+#[derive(Debug, Clone, Serialize)]
+pub struct PackageInfo {
+    #[serde(flatten)]
+    pub meta: cargo_metadata::Package,
+    pub enabled_features: Vec<String>,
+    pub footprint: Option<PackageFootprint>,
+}
+
+impl PackageInfo {
+    pub fn new(meta: &cargo_metadata::Package, package: &Package) -> Self {
+        Self {
+            meta: meta.clone(),
+            enabled_features: package.features.clone(),
+            footprint: package.footprint,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize)]
 pub struct DepGraphNode {
@@ -50,3 +69,12 @@ pub struct DepGraphInfo {
     pub nodes: Vec<DepGraphNode>,
     pub edges: Vec<DepGraphEdge>,
 }
+
+impl From<&DepGraph> for DepGraphInfo {
+    fn from(graph: &DepGraph) -> Self {
+        Self {
+            nodes: graph.node_weights().cloned().map(Into::into).collect(),
+            edges: graph.edge_weights().cloned().map(Into::into).collect(),
+        }
+    }
+}
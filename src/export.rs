@@ -0,0 +1,247 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use cargo_metadata::camino::Utf8Path;
+use nanoid::nanoid;
+
+use self::table::Separator;
+use crate::{
+    dep_info::DepInfoInner,
+    graph::{DepGraph, DepMap},
+    package::Package,
+};
+
+mod attrs;
+mod cyclonedx;
+mod dot;
+mod gexf;
+mod graphml;
+mod html;
+mod json;
+mod mermaid;
+mod sbom;
+mod spdx;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod table;
+mod tree;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ExportFormat {
+    Dot,
+    Json,
+    Mermaid,
+    GraphMl,
+    Gexf,
+    Html,
+    CycloneDxJson,
+    CycloneDxXml,
+    SpdxJson,
+    Tree,
+    Csv,
+    Tsv,
+    #[cfg(feature = "sqlite")]
+    Sqlite,
+}
+
+impl ExportFormat {
+    /// Values accepted by `--export`
+    pub const ALL: &'static [(&'static str, Self)] = &[
+        ("dot", Self::Dot),
+        ("json", Self::Json),
+        ("mermaid", Self::Mermaid),
+        ("graphml", Self::GraphMl),
+        ("gexf", Self::Gexf),
+        ("html", Self::Html),
+        ("cyclonedx-json", Self::CycloneDxJson),
+        ("cyclonedx-xml", Self::CycloneDxXml),
+        ("spdx-json", Self::SpdxJson),
+        ("tree", Self::Tree),
+        ("csv", Self::Csv),
+        ("tsv", Self::Tsv),
+        #[cfg(feature = "sqlite")]
+        ("sqlite", Self::Sqlite),
+    ];
+
+    /// Whether the format includes all attributes of packages, including their graph metrics
+    pub fn includes_metrics(self) -> bool {
+        match self {
+            Self::Json | Self::GraphMl | Self::Gexf | Self::Html | Self::Csv | Self::Tsv => true,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite => true,
+            Self::Dot
+            | Self::Mermaid
+            | Self::CycloneDxJson
+            | Self::CycloneDxXml
+            | Self::SpdxJson
+            | Self::Tree => false,
+        }
+    }
+
+    /// Panics on names that are not in `ALL`, clap validates them before we get here
+    pub fn from_name(name: &str) -> Self {
+        Self::ALL
+            .iter()
+            .find_map(|(n, format)| (*n == name).then_some(*format))
+            .unwrap_or_else(|| unreachable!("unknown export format {name}"))
+    }
+}
+
+pub(crate) fn export(
+    graph: &DepGraph,
+    depmap: &DepMap,
+    workspace_root: &Utf8Path,
+    format: ExportFormat,
+    output: Option<&str>,
+) -> anyhow::Result<()> {
+    // Formats consisting of multiple files are written to a directory, databases can't be
+    // written to stdout
+    let table_dir = Path::new(output.unwrap_or("."));
+    match format {
+        ExportFormat::Csv => return table::write(table_dir, graph, depmap, Separator::Comma),
+        ExportFormat::Tsv => return table::write(table_dir, graph, depmap, Separator::Tab),
+        #[cfg(feature = "sqlite")]
+        ExportFormat::Sqlite => {
+            let path = output.context("--export sqlite requires the database path given by -o")?;
+            return replace_file(Path::new(path), |tmp| sqlite::write(tmp, graph, depmap));
+        }
+        _ => {}
+    }
+
+    // Rendered in memory first, so that errors don't leave a partial file behind
+    let mut out = Vec::new();
+    match format {
+        ExportFormat::Dot => dot::write(&mut out, graph)?,
+        ExportFormat::Json => json::write(&mut out, graph)?,
+        ExportFormat::Mermaid => mermaid::write(&mut out, graph)?,
+        ExportFormat::GraphMl => graphml::write(&mut out, graph)?,
+        ExportFormat::Gexf => gexf::write(&mut out, graph)?,
+        ExportFormat::Html => html::write(&mut out, graph, depmap)?,
+        ExportFormat::CycloneDxJson => {
+            cyclonedx::write_json(&mut out, graph, depmap, workspace_root)?;
+        }
+        ExportFormat::CycloneDxXml => {
+            cyclonedx::write_xml(&mut out, graph, depmap, workspace_root)?;
+        }
+        ExportFormat::SpdxJson => spdx::write_json(&mut out, graph, depmap, workspace_root)?,
+        ExportFormat::Tree => tree::write(&mut out, graph)?,
+        ExportFormat::Csv | ExportFormat::Tsv => unreachable!("handled above"),
+        #[cfg(feature = "sqlite")]
+        ExportFormat::Sqlite => unreachable!("handled above"),
+    }
+
+    match output {
+        Some(path) => replace_file(Path::new(path), |tmp| Ok(fs::write(tmp, &out)?)),
+        None => Ok(io::stdout().lock().write_all(&out)?),
+    }
+}
+
+/// Writes a file through `write` at a temporary path next to `path`, which is only moved into
+/// place once it has been written completely
+pub(super) fn replace_file(
+    path: &Path,
+    write: impl FnOnce(&Path) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let name = path.file_name().with_context(|| format!("{} is not a file", path.display()))?;
+    let mut tmp_name = format!(".{}.", name.to_string_lossy());
+    tmp_name.push_str(&nanoid!(6));
+    tmp_name.push_str(".tmp");
+    let tmp = PathBuf::from(path).with_file_name(tmp_name);
+
+    let res =
+        write(&tmp).with_context(|| format!("Failed to write {}", path.display())).and_then(|()| {
+            fs::rename(&tmp, path).with_context(|| format!("Failed to create {}", path.display()))
+        });
+    if res.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    res
+}
+
+// Keep in sync with frontend/src/constants.ts
+const DEV_DEP_COLOR: &str = "orange";
+const BUILD_DEP_COLOR: &str = "skyblue";
+const NORMAL_DEP_COLOR: &str = "purple";
+
+/// Node background, same colors as in the web frontend (see README.md)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum NodeBackground {
+    WsMember,
+    OptionalTarget,
+    Optional,
+    Target,
+    Default,
+}
+
+impl NodeBackground {
+    const ALL: [Self; 5] =
+        [Self::WsMember, Self::OptionalTarget, Self::Optional, Self::Target, Self::Default];
+
+    fn of(pkg: &Package) -> Self {
+        let info = &pkg.dep_info;
+        if pkg.is_ws_member {
+            Self::WsMember
+        } else if info.is_optional && info.is_target_dep {
+            Self::OptionalTarget
+        } else if info.is_optional {
+            Self::Optional
+        } else if info.is_target_dep {
+            Self::Target
+        } else {
+            Self::Default
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::WsMember => "ws_member",
+            Self::OptionalTarget => "optional_target",
+            Self::Optional => "optional",
+            Self::Target => "target",
+            Self::Default => "default",
+        }
+    }
+
+    fn color(self) -> &'static str {
+        match self {
+            Self::WsMember => "cyan",
+            Self::OptionalTarget => "#93b48b",
+            Self::Optional => "#bcebcb",
+            Self::Target => "#aaaaaa",
+            Self::Default => "white",
+        }
+    }
+}
+
+/// Same edge colors as the web frontend
+fn kind_color(info: &DepInfoInner) -> Option<&'static str> {
+    if info.is_dev {
+        Some(DEV_DEP_COLOR)
+    } else if info.is_build {
+        Some(BUILD_DEP_COLOR)
+    } else if info.is_normal {
+        Some(NORMAL_DEP_COLOR)
+    } else {
+        None
+    }
+}
+
+/// Escapes text for use in XML attribute values and character data
+pub(crate) fn xml_escape(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '"' => res.push_str("&quot;"),
+            '\'' => res.push_str("&apos;"),
+            _ => res.push(c),
+        }
+    }
+    res
+}
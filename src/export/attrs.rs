@@ -0,0 +1,194 @@
+//! Typed node and edge attributes shared by the exporters of attributed graph formats.
+
+use std::fmt::{self, Display, Formatter};
+
+use cargo_metadata::Package as MetaPackage;
+
+use crate::{
+    dep_info::{BuildFlag, DepInfo, DepInfoInner},
+    diff::DiffStatus,
+    footprint::PackageFootprint,
+    package::Package,
+    report::licenses::Verdict,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AttrType {
+    Bool,
+    Int,
+    Float,
+    String,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum AttrValue {
+    Bool(bool),
+    Int(u64),
+    Float(f64),
+    String(String),
+    /// Formats without typed nulls use an empty value
+    Null,
+}
+
+impl AttrType {
+    /// Type name in GraphML and GEXF
+    pub fn xml_name(self) -> &'static str {
+        match self {
+            Self::Bool => "boolean",
+            Self::Int => "long",
+            Self::Float => "double",
+            Self::String => "string",
+        }
+    }
+
+    /// Column type in SQLite, every attribute but booleans may be missing
+    #[cfg(feature = "sqlite")]
+    pub fn sql_type(self) -> &'static str {
+        match self {
+            Self::Bool => "INTEGER NOT NULL",
+            Self::Int => "INTEGER",
+            Self::Float => "REAL",
+            Self::String => "TEXT",
+        }
+    }
+}
+
+impl Display for AttrValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(v) => write!(f, "{v}"),
+            Self::Int(v) => write!(f, "{v}"),
+            Self::Float(v) => write!(f, "{v}"),
+            Self::String(v) => f.write_str(v),
+            Self::Null => Ok(()),
+        }
+    }
+}
+
+pub(crate) type Attr<T> = (&'static str, AttrType, fn(&T) -> AttrValue);
+
+/// Names and values of the attributes of `v` that are not null. GraphML and GEXF express nulls by
+/// leaving the value out, as an empty value is invalid for numeric types.
+pub(crate) fn xml_values<'a, T>(
+    attrs: &'a [Attr<T>],
+    v: &'a T,
+) -> impl Iterator<Item = (&'static str, AttrValue)> + 'a {
+    attrs
+        .iter()
+        .map(move |(name, _, get)| (*name, get(v)))
+        .filter(|(_, value)| !matches!(value, AttrValue::Null))
+}
+
+/// Attributes of `Package`, its `dep_info` is described by `INFO_ATTRS`
+pub(crate) const NODE_ATTRS: &[Attr<Package>] = &[
+    ("name", AttrType::String, |p| AttrValue::String(p.name.clone())),
+    ("version", AttrType::String, |p| AttrValue::String(p.version.to_string())),
+    ("is_ws_member", AttrType::Bool, |p| AttrValue::Bool(p.is_ws_member)),
+    ("is_proc_macro", AttrType::Bool, |p| AttrValue::Bool(p.is_proc_macro)),
+    ("feature", AttrType::String, |p| optional_string(p.feature.as_ref())),
+    ("features", AttrType::String, |p| AttrValue::String(p.features.join(","))),
+    ("platforms", AttrType::String, |p| AttrValue::String(p.platforms.join(","))),
+    ("diff", AttrType::String, |p| diff_status(p.diff)),
+    ("previous_version", AttrType::String, |p| optional_string(p.previous_version.as_ref())),
+    ("name_uses", AttrType::Int, |p| AttrValue::Int(p.name_use_count().into())),
+    ("exclusive_deps", AttrType::Int, |p| AttrValue::Int(p.exclusive_deps.into())),
+    ("shared_deps", AttrType::Int, |p| AttrValue::Int(p.shared_deps.into())),
+    ("advisories", AttrType::String, |p| {
+        let ids: Vec<_> = p.advisories.iter().map(|a| a.id.as_str()).collect();
+        AttrValue::String(ids.join(","))
+    }),
+    ("advisory_kind", AttrType::String, |p| {
+        p.advisories
+            .iter()
+            .map(|a| a.kind)
+            .max()
+            .map_or(AttrValue::Null, |kind| AttrValue::String(kind.as_str().to_owned()))
+    }),
+    ("latest_compatible", AttrType::String, |p| {
+        optional_string(p.registry.as_ref().and_then(|r| r.latest_compatible.as_ref()))
+    }),
+    ("latest", AttrType::String, |p| {
+        optional_string(p.registry.as_ref().and_then(|r| r.latest.as_ref()))
+    }),
+    ("yanked", AttrType::Bool, |p| AttrValue::Bool(p.registry.as_ref().is_some_and(|r| r.yanked))),
+    ("rust_lines", AttrType::Int, |p| footprint(p, |f| f.own.rust_lines)),
+    ("exclusive_rust_lines", AttrType::Int, |p| footprint(p, |f| f.exclusive.rust_lines)),
+    ("total_rust_lines", AttrType::Int, |p| footprint(p, |f| f.total.rust_lines)),
+    ("total_bytes", AttrType::Int, |p| footprint(p, |f| f.total.bytes)),
+    ("unsafe_items", AttrType::Int, |p| {
+        p.unsafe_code.map_or(AttrValue::Null, |u| AttrValue::Int(u.own.items()))
+    }),
+    ("total_unsafe_items", AttrType::Int, |p| {
+        p.unsafe_code.map_or(AttrValue::Null, |u| AttrValue::Int(u.total.items()))
+    }),
+    ("forbids_unsafe", AttrType::Bool, |p| {
+        AttrValue::Bool(p.unsafe_code.is_some_and(|u| u.forbids_unsafe))
+    }),
+    ("build_time_ms", AttrType::Int, |p| {
+        p.build_time.map_or(AttrValue::Null, |t| AttrValue::Int((t * 1000.0).round() as u64))
+    }),
+    ("on_critical_path", AttrType::Bool, |p| AttrValue::Bool(p.on_critical_path)),
+    ("in_degree", AttrType::Int, |p| AttrValue::Int(p.metrics.in_degree.into())),
+    ("out_degree", AttrType::Int, |p| AttrValue::Int(p.metrics.out_degree.into())),
+    ("depth", AttrType::Int, |p| {
+        p.metrics.depth.map_or(AttrValue::Null, |d| AttrValue::Int(d.into()))
+    }),
+    ("paths", AttrType::Int, |p| AttrValue::Int(p.metrics.paths)),
+    ("betweenness", AttrType::Float, |p| AttrValue::Float(p.metrics.betweenness)),
+    ("reverse_deps", AttrType::Int, |p| AttrValue::Int(p.metrics.reverse_deps.into())),
+    ("license_verdict", AttrType::String, |p| match p.license_verdict {
+        Some(Verdict::Allow) => AttrValue::String("allow".to_owned()),
+        Some(Verdict::Review) => AttrValue::String("review".to_owned()),
+        Some(Verdict::Deny) => AttrValue::String("deny".to_owned()),
+        None => AttrValue::Null,
+    }),
+];
+
+/// Attributes of `DepInfo`, its `inner` is described by `INFO_ATTRS`
+pub(crate) const EDGE_ATTRS: &[Attr<DepInfo>] = &[
+    ("edge_no", AttrType::Int, |d| AttrValue::Int(d.edge_no.into())),
+    ("features", AttrType::String, |d| AttrValue::String(d.features.join(","))),
+    ("uses_default_features", AttrType::Bool, |d| AttrValue::Bool(d.uses_default_features)),
+    ("platform", AttrType::String, |d| optional_string(d.platform.as_ref())),
+    ("diff", AttrType::String, |d| diff_status(d.diff)),
+    ("in_cycle", AttrType::Bool, |d| AttrValue::Bool(d.in_cycle)),
+];
+
+/// Attributes of `DepInfoInner`, which both nodes and edges carry.
+///
+/// `visited` is left out since it is only bookkeeping for `update_dep_info`.
+pub(crate) const INFO_ATTRS: &[Attr<DepInfoInner>] = &[
+    ("kind_host", AttrType::String, |i| build_flag(i.kind.host)),
+    ("kind_target", AttrType::String, |i| build_flag(i.kind.target)),
+    ("is_target_dep", AttrType::Bool, |i| AttrValue::Bool(i.is_target_dep)),
+    ("is_optional", AttrType::Bool, |i| AttrValue::Bool(i.is_optional)),
+    ("is_optional_direct", AttrType::Bool, |i| AttrValue::Bool(i.is_optional_direct)),
+    ("is_dev", AttrType::Bool, |i| AttrValue::Bool(i.is_dev)),
+    ("is_build", AttrType::Bool, |i| AttrValue::Bool(i.is_build)),
+    ("is_normal", AttrType::Bool, |i| AttrValue::Bool(i.is_normal)),
+];
+
+/// Selected fields of the `cargo metadata` output for a package
+pub(crate) const META_ATTRS: &[Attr<MetaPackage>] = &[
+    ("license", AttrType::String, |p| optional_string(p.license.as_ref())),
+    ("repository", AttrType::String, |p| optional_string(p.repository.as_ref())),
+    ("rust_version", AttrType::String, |p| optional_string(p.rust_version.as_ref())),
+    ("edition", AttrType::String, |p| AttrValue::String(p.edition.to_string())),
+    ("source", AttrType::String, |p| optional_string(p.source.as_ref())),
+];
+
+fn footprint(pkg: &Package, f: fn(&PackageFootprint) -> u64) -> AttrValue {
+    pkg.footprint.as_ref().map_or(AttrValue::Null, |footprint| AttrValue::Int(f(footprint)))
+}
+
+fn optional_string(v: Option<&impl Display>) -> AttrValue {
+    v.map_or(AttrValue::Null, |v| AttrValue::String(v.to_string()))
+}
+
+fn diff_status(diff: Option<DiffStatus>) -> AttrValue {
+    diff.map_or(AttrValue::Null, |diff| AttrValue::String(diff.as_str().to_owned()))
+}
+
+fn build_flag(flag: BuildFlag) -> AttrValue {
+    AttrValue::String(format!("{flag:?}"))
+}
@@ -0,0 +1,295 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use cargo_metadata::{camino::Utf8Path, semver::Version, Package as MetaPackage, TargetKind};
+use serde_json::{json, Value};
+
+use super::{
+    sbom::{direct_deps, license_expression, lockfile_checksums, purl, SourceKind},
+    xml_escape,
+};
+use crate::{
+    graph::{DepGraph, DepMap},
+    package::Package,
+};
+
+const SPEC_VERSION: &str = "1.5";
+
+/// A package of the graph along with everything needed to describe it as a CycloneDX component
+struct Component<'a> {
+    node: &'a Package,
+    meta: &'a MetaPackage,
+    checksum: Option<&'a str>,
+}
+
+impl Component<'_> {
+    fn ty(&self) -> &'static str {
+        let is_bin = self.meta.targets.iter().any(|t| t.kind.contains(&TargetKind::Bin));
+        if is_bin {
+            "application"
+        } else {
+            "library"
+        }
+    }
+
+    /// Workspace members have no scope, dev-only dependencies are not part of the built artifact
+    fn scope(&self) -> Option<&'static str> {
+        let info = &self.node.dep_info;
+        if self.node.is_ws_member {
+            None
+        } else if info.kind.is_dev_only() {
+            Some("excluded")
+        } else if info.is_optional {
+            Some("optional")
+        } else {
+            Some("required")
+        }
+    }
+
+    fn source(&self) -> String {
+        match SourceKind::of(self.meta) {
+            SourceKind::Path => "path".to_owned(),
+            _ => self.meta.source.as_ref().map(|s| s.repr.clone()).unwrap_or_default(),
+        }
+    }
+
+    fn license(&self) -> Option<License> {
+        if let Some(expr) = &self.meta.license {
+            Some(License::Expression(license_expression(expr)))
+        } else {
+            let file = self.meta.license_file.as_ref()?;
+            Some(License::Name(format!("LicenseRef-{}", file.file_name().unwrap_or("LICENSE"))))
+        }
+    }
+
+    fn external_references(&self) -> Vec<(&'static str, &str)> {
+        let mut res = Vec::new();
+        if let Some(url) = &self.meta.repository {
+            res.push(("vcs", url.as_str()));
+        }
+        if let Some(url) = &self.meta.homepage {
+            res.push(("website", url.as_str()));
+        }
+        if let Some(url) = &self.meta.documentation {
+            res.push(("documentation", url.as_str()));
+        }
+        res
+    }
+}
+
+enum License {
+    Expression(String),
+    Name(String),
+}
+
+struct Bom<'a> {
+    /// The component the BOM describes, if the graph has a single root
+    root: Option<Component<'a>>,
+    components: Vec<Component<'a>>,
+    dependencies: Vec<(&'a str, Vec<&'a str>)>,
+}
+
+fn collect<'a>(
+    graph: &'a DepGraph,
+    depmap: &'a DepMap,
+    checksums: &'a HashMap<(String, Version), String>,
+) -> Bom<'a> {
+    let mut components: Vec<_> = graph
+        .node_weights()
+        .filter_map(|node| {
+            let meta = depmap.get(&node.id)?;
+            let checksum =
+                checksums.get(&(meta.name.clone(), meta.version.clone())).map(String::as_str);
+            Some(Component { node, meta, checksum })
+        })
+        .collect();
+
+    let root = match components.iter().filter(|c| c.node.is_ws_member).count() {
+        1 => {
+            let idx = components.iter().position(|c| c.node.is_ws_member).unwrap();
+            Some(components.remove(idx))
+        }
+        _ => None,
+    };
+
+    let mut dependencies: Vec<_> = direct_deps(graph).into_iter().collect();
+    dependencies.sort_unstable();
+
+    Bom { root, components, dependencies }
+}
+
+pub(crate) fn write_json(
+    out: &mut dyn Write,
+    graph: &DepGraph,
+    depmap: &DepMap,
+    workspace_root: &Utf8Path,
+) -> io::Result<()> {
+    let checksums = lockfile_checksums(workspace_root);
+    let bom = collect(graph, depmap, &checksums);
+
+    let mut metadata = json!({
+        "tools": {
+            "components": [{
+                "type": "application",
+                "name": "cargo-visualize",
+                "version": env!("CARGO_PKG_VERSION"),
+            }],
+        },
+    });
+    if let Some(root) = &bom.root {
+        metadata["component"] = component_json(root);
+    }
+
+    let doc = json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": SPEC_VERSION,
+        "version": 1,
+        "metadata": metadata,
+        "components": bom.components.iter().map(component_json).collect::<Vec<_>>(),
+        "dependencies": bom
+            .dependencies
+            .iter()
+            .map(|(id, deps)| json!({ "ref": id, "dependsOn": deps }))
+            .collect::<Vec<_>>(),
+    });
+
+    serde_json::to_writer_pretty(&mut *out, &doc)?;
+    writeln!(out)
+}
+
+fn component_json(c: &Component<'_>) -> Value {
+    let mut res = json!({
+        "type": c.ty(),
+        "bom-ref": c.node.id,
+        "name": c.meta.name,
+        "version": c.meta.version.to_string(),
+        "purl": purl(c.meta),
+        "properties": [{ "name": "cargo:source", "value": c.source() }],
+    });
+    if let Some(description) = &c.meta.description {
+        res["description"] = json!(description);
+    }
+    if let Some(scope) = c.scope() {
+        res["scope"] = json!(scope);
+    }
+    match c.license() {
+        Some(License::Expression(expr)) => res["licenses"] = json!([{ "expression": expr }]),
+        Some(License::Name(name)) => res["licenses"] = json!([{ "license": { "name": name } }]),
+        None => {}
+    }
+    if let Some(checksum) = c.checksum {
+        res["hashes"] = json!([{ "alg": "SHA-256", "content": checksum }]);
+    }
+    let refs = c.external_references();
+    if !refs.is_empty() {
+        res["externalReferences"] = refs
+            .into_iter()
+            .map(|(ty, url)| json!({ "type": ty, "url": url }))
+            .collect::<Vec<_>>()
+            .into();
+    }
+    res
+}
+
+pub(crate) fn write_xml(
+    out: &mut dyn Write,
+    graph: &DepGraph,
+    depmap: &DepMap,
+    workspace_root: &Utf8Path,
+) -> io::Result<()> {
+    let checksums = lockfile_checksums(workspace_root);
+    let bom = collect(graph, depmap, &checksums);
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, r#"<bom xmlns="http://cyclonedx.org/schema/bom/{SPEC_VERSION}" version="1">"#)?;
+
+    writeln!(out, "  <metadata>")?;
+    writeln!(out, "    <tools>")?;
+    writeln!(out, "      <components>")?;
+    writeln!(out, r#"        <component type="application">"#)?;
+    writeln!(out, "          <name>cargo-visualize</name>")?;
+    writeln!(out, "          <version>{}</version>", env!("CARGO_PKG_VERSION"))?;
+    writeln!(out, "        </component>")?;
+    writeln!(out, "      </components>")?;
+    writeln!(out, "    </tools>")?;
+    if let Some(root) = &bom.root {
+        write_component_xml(out, root, "    ")?;
+    }
+    writeln!(out, "  </metadata>")?;
+
+    writeln!(out, "  <components>")?;
+    for c in &bom.components {
+        write_component_xml(out, c, "    ")?;
+    }
+    writeln!(out, "  </components>")?;
+
+    writeln!(out, "  <dependencies>")?;
+    for (id, deps) in &bom.dependencies {
+        if deps.is_empty() {
+            writeln!(out, r#"    <dependency ref="{}"/>"#, xml_escape(id))?;
+            continue;
+        }
+        writeln!(out, r#"    <dependency ref="{}">"#, xml_escape(id))?;
+        for dep in deps {
+            writeln!(out, r#"      <dependency ref="{}"/>"#, xml_escape(dep))?;
+        }
+        writeln!(out, "    </dependency>")?;
+    }
+    writeln!(out, "  </dependencies>")?;
+
+    writeln!(out, "</bom>")
+}
+
+fn write_component_xml(out: &mut dyn Write, c: &Component<'_>, indent: &str) -> io::Result<()> {
+    // Element order is mandated by the XML schema
+    writeln!(out, r#"{indent}<component type="{}" bom-ref="{}">"#, c.ty(), xml_escape(&c.node.id))?;
+    writeln!(out, "{indent}  <name>{}</name>", xml_escape(&c.meta.name))?;
+    writeln!(out, "{indent}  <version>{}</version>", c.meta.version)?;
+    if let Some(description) = &c.meta.description {
+        writeln!(out, "{indent}  <description>{}</description>", xml_escape(description))?;
+    }
+    if let Some(scope) = c.scope() {
+        writeln!(out, "{indent}  <scope>{scope}</scope>")?;
+    }
+    if let Some(checksum) = c.checksum {
+        writeln!(out, "{indent}  <hashes>")?;
+        writeln!(out, r#"{indent}    <hash alg="SHA-256">{checksum}</hash>"#)?;
+        writeln!(out, "{indent}  </hashes>")?;
+    }
+    match c.license() {
+        Some(License::Expression(expr)) => {
+            writeln!(out, "{indent}  <licenses>")?;
+            writeln!(out, "{indent}    <expression>{}</expression>", xml_escape(&expr))?;
+            writeln!(out, "{indent}  </licenses>")?;
+        }
+        Some(License::Name(name)) => {
+            writeln!(out, "{indent}  <licenses>")?;
+            writeln!(out, "{indent}    <license>")?;
+            writeln!(out, "{indent}      <name>{}</name>", xml_escape(&name))?;
+            writeln!(out, "{indent}    </license>")?;
+            writeln!(out, "{indent}  </licenses>")?;
+        }
+        None => {}
+    }
+    writeln!(out, "{indent}  <purl>{}</purl>", xml_escape(&purl(c.meta)))?;
+    let refs = c.external_references();
+    if !refs.is_empty() {
+        writeln!(out, "{indent}  <externalReferences>")?;
+        for (ty, url) in refs {
+            writeln!(out, r#"{indent}    <reference type="{ty}">"#)?;
+            writeln!(out, "{indent}      <url>{}</url>", xml_escape(url))?;
+            writeln!(out, "{indent}    </reference>")?;
+        }
+        writeln!(out, "{indent}  </externalReferences>")?;
+    }
+    writeln!(out, "{indent}  <properties>")?;
+    writeln!(
+        out,
+        r#"{indent}    <property name="cargo:source">{}</property>"#,
+        xml_escape(&c.source())
+    )?;
+    writeln!(out, "{indent}  </properties>")?;
+    writeln!(out, "{indent}</component>")
+}
@@ -0,0 +1,58 @@
+use std::io::{self, Write};
+
+use petgraph::dot::{Config, Dot};
+
+use super::{kind_color, NodeBackground};
+use crate::{dep_info::DepInfoInner, graph::DepGraph, package::Package};
+
+pub(crate) fn write(out: &mut dyn Write, graph: &DepGraph) -> io::Result<()> {
+    let dot = Dot::with_attr_getters(
+        graph,
+        &[Config::EdgeNoLabel],
+        &|_, edge| edge_attrs(&edge.weight().inner),
+        &|_, (_, pkg)| node_attrs(pkg),
+    );
+
+    writeln!(out, "{dot:?}")
+}
+
+fn node_attrs(pkg: &Package) -> String {
+    let info = &pkg.dep_info;
+    let mut attrs = vec!["style = filled".to_owned()];
+
+    if pkg.is_ws_member {
+        attrs.push("shape = box".to_owned());
+    }
+    if pkg.is_proc_macro {
+        attrs.push("peripheries = 2".to_owned());
+    }
+
+    attrs.push(format!("fillcolor = \"{}\"", NodeBackground::of(pkg).color()));
+
+    // Only highlight the border of packages that are not normal dependencies of anything
+    if !pkg.is_ws_member && !info.is_normal {
+        if let Some(color) = kind_color(info) {
+            attrs.push(format!("color = {color}"));
+        }
+    }
+
+    attrs.join(", ")
+}
+
+fn edge_attrs(info: &DepInfoInner) -> String {
+    let mut attrs = Vec::new();
+
+    if let Some(color) = kind_color(info) {
+        attrs.push(format!("color = {color}"));
+    }
+    if info.is_target_dep {
+        attrs.push("arrowhead = empty".to_owned());
+    }
+    if info.is_optional_direct {
+        attrs.push("style = dotted".to_owned());
+    } else if info.is_optional {
+        attrs.push("style = dashed".to_owned());
+    }
+
+    attrs.join(", ")
+}
@@ -0,0 +1,77 @@
+use std::io::{self, Write};
+
+use super::{
+    attrs::{xml_values, Attr, EDGE_ATTRS, INFO_ATTRS, NODE_ATTRS},
+    xml_escape,
+};
+use crate::graph::DepGraph;
+
+pub(crate) fn write(out: &mut dyn Write, graph: &DepGraph) -> io::Result<()> {
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, r#"<gexf xmlns="http://gexf.net/1.3" version="1.3">"#)?;
+    writeln!(out, "  <meta>")?;
+    writeln!(out, "    <creator>cargo-visualize {}</creator>", env!("CARGO_PKG_VERSION"))?;
+    writeln!(out, "  </meta>")?;
+    writeln!(out, r#"  <graph defaultedgetype="directed" mode="static">"#)?;
+
+    writeln!(out, r#"    <attributes class="node">"#)?;
+    write_attributes(out, NODE_ATTRS)?;
+    write_attributes(out, INFO_ATTRS)?;
+    writeln!(out, "    </attributes>")?;
+    writeln!(out, r#"    <attributes class="edge">"#)?;
+    write_attributes(out, EDGE_ATTRS)?;
+    write_attributes(out, INFO_ATTRS)?;
+    writeln!(out, "    </attributes>")?;
+
+    writeln!(out, "    <nodes>")?;
+    for pkg in graph.node_weights() {
+        writeln!(
+            out,
+            r#"      <node id="{}" label="{}">"#,
+            xml_escape(&pkg.id),
+            xml_escape(&format!("{pkg:?}")),
+        )?;
+        writeln!(out, "        <attvalues>")?;
+        write_attvalues(out, NODE_ATTRS, pkg)?;
+        write_attvalues(out, INFO_ATTRS, &pkg.dep_info)?;
+        writeln!(out, "        </attvalues>")?;
+        writeln!(out, "      </node>")?;
+    }
+    writeln!(out, "    </nodes>")?;
+
+    writeln!(out, "    <edges>")?;
+    for dep in graph.edge_weights() {
+        writeln!(
+            out,
+            r#"      <edge id="{}" source="{}" target="{}">"#,
+            xml_escape(&dep.id),
+            xml_escape(&dep.source),
+            xml_escape(&dep.target),
+        )?;
+        writeln!(out, "        <attvalues>")?;
+        write_attvalues(out, EDGE_ATTRS, dep)?;
+        write_attvalues(out, INFO_ATTRS, &dep.inner)?;
+        writeln!(out, "        </attvalues>")?;
+        writeln!(out, "      </edge>")?;
+    }
+    writeln!(out, "    </edges>")?;
+
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</gexf>")
+}
+
+fn write_attributes<T>(out: &mut dyn Write, attrs: &[Attr<T>]) -> io::Result<()> {
+    for (name, ty, _) in attrs {
+        let ty = ty.xml_name();
+        writeln!(out, r#"      <attribute id="{name}" title="{name}" type="{ty}"/>"#)?;
+    }
+    Ok(())
+}
+
+fn write_attvalues<T>(out: &mut dyn Write, attrs: &[Attr<T>], v: &T) -> io::Result<()> {
+    for (name, value) in xml_values(attrs, v) {
+        let value = xml_escape(&value.to_string());
+        writeln!(out, r#"          <attvalue for="{name}" value="{value}"/>"#)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use std::io::{self, Write};
+
+use super::{
+    attrs::{xml_values, Attr, EDGE_ATTRS, INFO_ATTRS, NODE_ATTRS},
+    xml_escape,
+};
+use crate::graph::DepGraph;
+
+pub(crate) fn write(out: &mut dyn Write, graph: &DepGraph) -> io::Result<()> {
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        out,
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd">"#
+    )?;
+
+    write_keys(out, "node", NODE_ATTRS)?;
+    write_keys(out, "node", INFO_ATTRS)?;
+    write_keys(out, "edge", EDGE_ATTRS)?;
+    write_keys(out, "edge", INFO_ATTRS)?;
+
+    writeln!(out, r#"  <graph id="G" edgedefault="directed">"#)?;
+    for pkg in graph.node_weights() {
+        writeln!(out, r#"    <node id="{}">"#, xml_escape(&pkg.id))?;
+        write_data(out, "node", NODE_ATTRS, pkg)?;
+        write_data(out, "node", INFO_ATTRS, &pkg.dep_info)?;
+        writeln!(out, "    </node>")?;
+    }
+    for dep in graph.edge_weights() {
+        writeln!(
+            out,
+            r#"    <edge id="{}" source="{}" target="{}">"#,
+            xml_escape(&dep.id),
+            xml_escape(&dep.source),
+            xml_escape(&dep.target),
+        )?;
+        write_data(out, "edge", EDGE_ATTRS, dep)?;
+        write_data(out, "edge", INFO_ATTRS, &dep.inner)?;
+        writeln!(out, "    </edge>")?;
+    }
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</graphml>")
+}
+
+fn write_keys<T>(out: &mut dyn Write, domain: &str, attrs: &[Attr<T>]) -> io::Result<()> {
+    for (name, ty, _) in attrs {
+        let ty = ty.xml_name();
+        writeln!(
+            out,
+            r#"  <key id="{domain}_{name}" for="{domain}" attr.name="{name}" attr.type="{ty}"/>"#
+        )?;
+    }
+    Ok(())
+}
+
+fn write_data<T>(out: &mut dyn Write, domain: &str, attrs: &[Attr<T>], v: &T) -> io::Result<()> {
+    for (name, value) in xml_values(attrs, v) {
+        let value = xml_escape(&value.to_string());
+        writeln!(out, r#"      <data key="{domain}_{name}">{value}</data>"#)?;
+    }
+    Ok(())
+}
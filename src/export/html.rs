@@ -0,0 +1,127 @@
+use std::{collections::HashMap, io::Write};
+
+use anyhow::{bail, Context};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use cfg_if::cfg_if;
+use serde::Serialize;
+
+use crate::{
+    dto::{DepGraphInfo, PackageInfo},
+    graph::{DepGraph, DepMap},
+};
+
+/// Everything the frontend would otherwise request from the web service.
+///
+/// Picked up by `frontend/src/api.ts`.
+#[derive(Serialize)]
+struct Snapshot<'a> {
+    graph: DepGraphInfo,
+    packages: HashMap<&'a str, PackageInfo>,
+}
+
+pub(crate) fn write(out: &mut dyn Write, graph: &DepGraph, depmap: &DepMap) -> anyhow::Result<()> {
+    let snapshot = Snapshot {
+        graph: DepGraphInfo::from(graph),
+        packages: graph
+            .node_weights()
+            .filter_map(|pkg| {
+                Some((pkg.id.as_str(), PackageInfo::new(depmap.get(pkg.package_id())?, pkg)))
+            })
+            .collect(),
+    };
+    // `</` can only appear inside of JSON strings, where `<\/` means the same thing but can't
+    // close the surrounding script tag.
+    let data = serde_json::to_string(&snapshot)?.replace("</", "<\\/");
+    let data = format!("<script>window.__CARGO_VISUALIZE_SNAPSHOT__ = {data};</script>\n");
+
+    let index = String::from_utf8(read_asset("index.html")?)
+        .context("index.html of the frontend is not valid UTF-8")?;
+    let html = inline_assets(&index)?;
+    let Some(head_end) = html.find("</head>") else {
+        bail!("index.html of the frontend has no </head>");
+    };
+
+    let (head, body) = html.split_at(head_end);
+    write!(out, "{head}{data}{body}")?;
+    Ok(())
+}
+
+/// Replaces all references to local assets in `index` with their content, so that the page works
+/// without the web service.
+fn inline_assets(index: &str) -> anyhow::Result<String> {
+    let mut res = String::with_capacity(index.len());
+    let mut rest = index;
+    while let Some(start) = rest.find('<') {
+        res.push_str(&rest[..start]);
+        let end = rest[start..].find('>').context("unterminated tag in index.html")? + start + 1;
+        let tag = &rest[start..end];
+        rest = &rest[end..];
+
+        if tag.starts_with("<script") {
+            if let Some(src) = local_attr(tag, "src") {
+                rest =
+                    rest.strip_prefix("</script>").context("expected </script> after <script>")?;
+                let js = String::from_utf8(read_asset(src)?)
+                    .with_context(|| format!("{src} is not valid UTF-8"))?;
+                res.push_str("<script type=\"module\">");
+                res.push_str(&js.replace("</script", "<\\/script"));
+                res.push_str("</script>");
+                continue;
+            }
+        } else if tag.starts_with("<link") {
+            if let Some(href) = local_attr(tag, "href") {
+                let content = read_asset(href)?;
+                if tag.contains("rel=\"stylesheet\"") {
+                    let css = String::from_utf8(content)
+                        .with_context(|| format!("{href} is not valid UTF-8"))?;
+                    res.push_str("<style>");
+                    res.push_str(&css);
+                    res.push_str("</style>");
+                } else {
+                    let data_uri =
+                        format!("data:{};base64,{}", mime(href), BASE64_STANDARD.encode(content));
+                    res.push_str(&tag.replacen(
+                        &format!("\"/{href}\""),
+                        &format!("\"{data_uri}\""),
+                        1,
+                    ));
+                }
+                continue;
+            }
+        }
+
+        res.push_str(tag);
+    }
+    res.push_str(rest);
+
+    Ok(res)
+}
+
+/// Value of attribute `name` in `tag` if it refers to a local asset, without the leading `/`
+fn local_attr<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let start = tag.find(&format!(" {name}=\"/"))? + name.len() + 4;
+    let len = tag[start..].find('"')?;
+    Some(&tag[start..start + len])
+}
+
+fn mime(path: &str) -> &'static str {
+    match path.rsplit_once('.').map(|(_, ext)| ext) {
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("ico") => "image/x-icon",
+        _ => "application/octet-stream",
+    }
+}
+
+fn read_asset(path: &str) -> anyhow::Result<Vec<u8>> {
+    cfg_if! {
+        if #[cfg(embed)] {
+            crate::assets::get(path)
+                .map(|data| data.into_owned())
+                .with_context(|| format!("asset {path} is not embedded"))
+        } else {
+            let path = std::path::Path::new(env!("__ASSET_DIR")).join(path);
+            std::fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))
+        }
+    }
+}
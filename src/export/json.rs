@@ -0,0 +1,9 @@
+use std::io::{self, Write};
+
+use crate::{dto::DepGraphInfo, graph::DepGraph};
+
+/// Writes the same payload that is served on `/graph`
+pub(crate) fn write(out: &mut dyn Write, graph: &DepGraph) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *out, &DepGraphInfo::from(graph))?;
+    writeln!(out)
+}
@@ -0,0 +1,101 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+use petgraph::visit::{EdgeRef, IntoEdgeReferences, IntoNodeReferences};
+
+use super::{kind_color, NodeBackground};
+use crate::{dep_info::DepInfoInner, graph::DepGraph, package::Package};
+
+pub(crate) fn write(out: &mut dyn Write, graph: &DepGraph) -> io::Result<()> {
+    writeln!(out, "flowchart TD")?;
+
+    let mut classes = BTreeMap::<NodeBackground, Vec<String>>::new();
+    for (idx, pkg) in graph.node_references() {
+        let id = format!("n{}", idx.index());
+        writeln!(out, "    {id}{}", node_shape(pkg))?;
+        classes.entry(NodeBackground::of(pkg)).or_default().push(id);
+    }
+
+    // Mermaid only supports styling links by their index, so collect the styles first and emit
+    // them grouped after all links have been written.
+    let mut link_styles = BTreeMap::<String, Vec<String>>::new();
+    for (i, edge) in graph.edge_references().enumerate() {
+        let info = &edge.weight().inner;
+        let arrow = if info.is_optional_direct { "-.->" } else { "-->" };
+        write!(out, "    n{} {arrow}", edge.source().index())?;
+        if let Some(label) = edge_label(info) {
+            write!(out, "|{label}|")?;
+        }
+        writeln!(out, " n{}", edge.target().index())?;
+
+        let mut style = Vec::new();
+        if let Some(color) = kind_color(info) {
+            style.push(format!("stroke:{color}"));
+        }
+        // Dashed lines for transitively optional dependencies, like in the web frontend
+        if info.is_optional && !info.is_optional_direct {
+            style.push("stroke-dasharray:6 3".to_owned());
+        }
+        if !style.is_empty() {
+            link_styles.entry(style.join(",")).or_default().push(i.to_string());
+        }
+    }
+
+    for (style, links) in link_styles {
+        writeln!(out, "    linkStyle {} {style}", links.join(","))?;
+    }
+
+    // `default` is applied to all nodes by mermaid, other classes override it
+    for background in NodeBackground::ALL {
+        writeln!(
+            out,
+            "    classDef {} fill:{},stroke:#222",
+            background.name(),
+            background.color()
+        )?;
+    }
+    writeln!(out, "    classDef proc_macro stroke-width:3px")?;
+    for (background, ids) in classes {
+        if background != NodeBackground::Default {
+            writeln!(out, "    class {} {}", ids.join(","), background.name())?;
+        }
+    }
+    let proc_macros: Vec<_> = graph
+        .node_references()
+        .filter(|(_, pkg)| pkg.is_proc_macro)
+        .map(|(idx, _)| format!("n{}", idx.index()))
+        .collect();
+    if !proc_macros.is_empty() {
+        writeln!(out, "    class {} proc_macro", proc_macros.join(","))?;
+    }
+
+    Ok(())
+}
+
+fn node_shape(pkg: &Package) -> String {
+    let label = format!("{pkg:?}");
+    if pkg.is_ws_member {
+        format!("[\"{label}\"]")
+    } else if pkg.is_proc_macro {
+        format!("{{{{\"{label}\"}}}}")
+    } else {
+        format!("(\"{label}\")")
+    }
+}
+
+fn edge_label(info: &DepInfoInner) -> Option<String> {
+    let mut labels = Vec::new();
+    if info.is_dev {
+        labels.push("dev");
+    }
+    if info.is_build {
+        labels.push("build");
+    }
+    if info.is_target_dep {
+        labels.push("target");
+    }
+
+    (!labels.is_empty()).then(|| labels.join(", "))
+}
@@ -0,0 +1,174 @@
+//! Helpers shared by the SBOM exporters.
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use cargo_metadata::{camino::Utf8Path, semver::Version, Package as MetaPackage};
+use petgraph::{
+    stable_graph::NodeIndex,
+    visit::{EdgeRef, IntoEdgeReferences},
+};
+use serde::Deserialize;
+
+use crate::{dep_info::DepInfo, graph::DepGraph};
+
+pub(crate) enum SourceKind<'a> {
+    CratesIo,
+    /// Any other registry, contains the index URL
+    Registry(&'a str),
+    /// Contains the repository URL including the revision fragment
+    Git(&'a str),
+    /// Local path dependencies and workspace members
+    Path,
+}
+
+impl<'a> SourceKind<'a> {
+    pub fn of(pkg: &'a MetaPackage) -> Self {
+        match &pkg.source {
+            None => Self::Path,
+            Some(source) if source.is_crates_io() => Self::CratesIo,
+            Some(source) => {
+                if let Some(url) = source.repr.strip_prefix("git+") {
+                    Self::Git(url)
+                } else if let Some(url) = source.repr.strip_prefix("registry+") {
+                    Self::Registry(url)
+                } else if let Some(url) = source.repr.strip_prefix("sparse+") {
+                    Self::Registry(url)
+                } else {
+                    Self::Registry(&source.repr)
+                }
+            }
+        }
+    }
+}
+
+/// The `license` of a package as an SPDX expression. Cargo still accepts the deprecated
+/// `MIT/Apache-2.0` syntax, which is not a valid one.
+pub(crate) fn license_expression(license: &str) -> String {
+    license.split('/').map(str::trim).collect::<Vec<_>>().join(" OR ")
+}
+
+/// Package URL as defined in <https://github.com/package-url/purl-spec>
+pub(crate) fn purl(pkg: &MetaPackage) -> String {
+    let mut purl = format!("pkg:cargo/{}@{}", percent_encode(&pkg.name), pkg.version);
+    match SourceKind::of(pkg) {
+        SourceKind::CratesIo => {}
+        SourceKind::Registry(url) => {
+            purl.push_str("?repository_url=");
+            purl.push_str(&percent_encode(url));
+        }
+        SourceKind::Git(url) => {
+            purl.push_str("?vcs_url=");
+            purl.push_str(&percent_encode(&format!("git+{url}")));
+        }
+        SourceKind::Path => {
+            if let Some(dir) = pkg.manifest_path.parent() {
+                purl.push_str("?download_url=");
+                purl.push_str(&percent_encode(&format!("file://{dir}")));
+            }
+        }
+    }
+    purl
+}
+
+fn percent_encode(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+            res.push(b as char);
+        } else {
+            res.push_str(&format!("%{b:02X}"));
+        }
+    }
+    res
+}
+
+/// Dependencies between packages, leaving out the edges of feature nodes
+pub(crate) fn package_deps(graph: &DepGraph) -> impl Iterator<Item = &DepInfo> {
+    graph.edge_references().filter_map(|edge| {
+        let is_package = |idx: NodeIndex<u16>| graph[idx].feature.is_none();
+        (is_package(edge.source()) && is_package(edge.target())).then_some(edge.weight())
+    })
+}
+
+/// Maps the id of every package to the ids of its direct dependencies, without duplicates
+pub(crate) fn direct_deps(graph: &DepGraph) -> HashMap<&str, Vec<&str>> {
+    let mut res: HashMap<_, Vec<_>> = graph
+        .node_weights()
+        .filter(|pkg| pkg.feature.is_none())
+        .map(|pkg| (pkg.id.as_str(), Vec::new()))
+        .collect();
+    for dep in package_deps(graph) {
+        let deps = res.entry(dep.source.as_str()).or_default();
+        if !deps.contains(&dep.target.as_str()) {
+            deps.push(dep.target.as_str());
+        }
+    }
+    res
+}
+
+#[derive(Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+#[derive(Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: Version,
+    checksum: Option<String>,
+}
+
+/// SHA-256 checksums of registry packages, as recorded in `Cargo.lock`
+///
+/// Returns an empty map if there is no readable lockfile.
+pub(crate) fn lockfile_checksums(workspace_root: &Utf8Path) -> HashMap<(String, Version), String> {
+    let Ok(lockfile) = fs::read_to_string(workspace_root.join("Cargo.lock")) else {
+        return HashMap::new();
+    };
+    let lockfile: Lockfile = match toml::from_str(&lockfile) {
+        Ok(lockfile) => lockfile,
+        Err(e) => {
+            eprintln!("Failed to parse Cargo.lock: {e}");
+            return HashMap::new();
+        }
+    };
+    lockfile
+        .package
+        .into_iter()
+        .filter_map(|pkg| Some(((pkg.name, pkg.version), pkg.checksum?)))
+        .collect()
+}
+
+/// Current time as an RFC 3339 UTC timestamp with second precision.
+///
+/// Honors `SOURCE_DATE_EPOCH` so that documents can be generated reproducibly.
+pub(crate) fn utc_timestamp() -> String {
+    let secs =
+        env::var("SOURCE_DATE_EPOCH").ok().and_then(|s| s.parse().ok()).unwrap_or_else(|| {
+            SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+        });
+
+    // Civil from days, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = (secs / 86400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    let time = secs % 86400;
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
@@ -0,0 +1,157 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    io::{self, Write},
+};
+
+use cargo_metadata::{camino::Utf8Path, Package as MetaPackage};
+use nanoid::nanoid;
+use serde_json::{json, Value};
+
+use super::sbom::{
+    license_expression, lockfile_checksums, package_deps, purl, utc_timestamp, SourceKind,
+};
+use crate::graph::{DepGraph, DepMap};
+
+const NOASSERTION: &str = "NOASSERTION";
+
+pub(crate) fn write_json(
+    out: &mut dyn Write,
+    graph: &DepGraph,
+    depmap: &DepMap,
+    workspace_root: &Utf8Path,
+) -> io::Result<()> {
+    let checksums = lockfile_checksums(workspace_root);
+
+    let mut packages = Vec::new();
+    let mut extracted_licenses = Vec::new();
+    let mut relationships = BTreeSet::new();
+    for node in graph.node_weights() {
+        let Some(meta) = depmap.get(&node.id) else {
+            continue;
+        };
+        let id = spdx_id(&node.id);
+
+        let license_declared = if let Some(expr) = &meta.license {
+            license_expression(expr)
+        } else if let Some(text) = license_file_text(meta) {
+            let license_id = format!("LicenseRef-{}", &id["SPDXRef-".len()..]);
+            extracted_licenses.push(json!({
+                "licenseId": license_id,
+                "extractedText": text,
+            }));
+            license_id
+        } else {
+            NOASSERTION.to_owned()
+        };
+
+        let mut package = json!({
+            "SPDXID": id,
+            "name": meta.name,
+            "versionInfo": meta.version.to_string(),
+            "downloadLocation": download_location(meta),
+            "filesAnalyzed": false,
+            "licenseConcluded": NOASSERTION,
+            "licenseDeclared": license_declared,
+            "copyrightText": NOASSERTION,
+            "externalRefs": [{
+                "referenceCategory": "PACKAGE-MANAGER",
+                "referenceType": "purl",
+                "referenceLocator": purl(meta),
+            }],
+        });
+        if let Some(checksum) = checksums.get(&(meta.name.clone(), meta.version.clone())) {
+            package["checksums"] = json!([{ "algorithm": "SHA256", "checksumValue": checksum }]);
+        }
+        if let Some(homepage) = meta.homepage.as_ref().or(meta.repository.as_ref()) {
+            package["homepage"] = json!(homepage);
+        }
+        if let Some(repository) = &meta.repository {
+            package["sourceInfo"] = json!(format!("repository: {repository}"));
+        }
+        if let Some(description) = &meta.description {
+            package["description"] = json!(description);
+        }
+        if !meta.authors.is_empty() {
+            package["originator"] = json!(format!("Person: {}", meta.authors.join(", ")));
+        }
+        packages.push(package);
+
+        if node.is_ws_member {
+            relationships.insert(("SPDXRef-DOCUMENT".to_owned(), "DESCRIBES", id));
+        }
+    }
+
+    for dep in package_deps(graph) {
+        let source = spdx_id(&dep.source);
+        let target = spdx_id(&dep.target);
+        let info = &dep.inner;
+        if info.is_normal {
+            relationships.insert((source.clone(), "DEPENDS_ON", target.clone()));
+        }
+        if info.is_dev {
+            relationships.insert((target.clone(), "DEV_DEPENDENCY_OF", source.clone()));
+        }
+        if info.is_build {
+            relationships.insert((target.clone(), "BUILD_DEPENDENCY_OF", source.clone()));
+        }
+        if info.is_optional_direct {
+            relationships.insert((target, "OPTIONAL_DEPENDENCY_OF", source));
+        }
+    }
+
+    let name = match graph.node_weights().filter(|pkg| pkg.is_ws_member).collect::<Vec<_>>()[..] {
+        [root] => root.id.replace(' ', "-"),
+        _ => workspace_root.file_name().unwrap_or("workspace").to_owned(),
+    };
+
+    let mut doc = json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": name,
+        "documentNamespace": format!("https://spdx.org/spdxdocs/{name}-{}", nanoid!()),
+        "creationInfo": {
+            "created": utc_timestamp(),
+            "creators": [format!("Tool: cargo-visualize-{}", env!("CARGO_PKG_VERSION"))],
+        },
+        "packages": packages,
+        "relationships": relationships
+            .into_iter()
+            .map(|(element, ty, related)| json!({
+                "spdxElementId": element,
+                "relationshipType": ty,
+                "relatedSpdxElement": related,
+            }))
+            .collect::<Vec<_>>(),
+    });
+    if !extracted_licenses.is_empty() {
+        doc["hasExtractedLicensingInfos"] = Value::Array(extracted_licenses);
+    }
+
+    serde_json::to_writer_pretty(&mut *out, &doc)?;
+    writeln!(out)
+}
+
+/// SPDX identifiers may only contain letters, numbers, `.` and `-`
+fn spdx_id(pkg_id: &str) -> String {
+    let sanitized: String = pkg_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '-' })
+        .collect();
+    format!("SPDXRef-Package-{sanitized}")
+}
+
+fn license_file_text(pkg: &MetaPackage) -> Option<String> {
+    fs::read_to_string(pkg.license_file()?).ok()
+}
+
+fn download_location(pkg: &MetaPackage) -> String {
+    match SourceKind::of(pkg) {
+        SourceKind::CratesIo => {
+            format!("https://crates.io/api/v1/crates/{}/{}/download", pkg.name, pkg.version)
+        }
+        SourceKind::Git(url) => format!("git+{url}"),
+        SourceKind::Registry(_) | SourceKind::Path => NOASSERTION.to_owned(),
+    }
+}
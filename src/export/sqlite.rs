@@ -0,0 +1,170 @@
+use std::path::Path;
+
+use anyhow::Context;
+use rusqlite::{params, types::Value, Connection};
+
+use super::attrs::{Attr, AttrValue, EDGE_ATTRS, INFO_ATTRS, META_ATTRS, NODE_ATTRS};
+use crate::graph::{DepGraph, DepMap};
+
+/// Writes the graph and the metadata of its packages into a new SQLite database at `path`
+pub(crate) fn write(path: &Path, graph: &DepGraph, depmap: &DepMap) -> anyhow::Result<()> {
+    let mut conn = Connection::open(path)
+        .with_context(|| format!("Failed to create database {}", path.display()))?;
+    let tx = conn.transaction()?;
+
+    let package_columns = [columns(NODE_ATTRS), columns(INFO_ATTRS), columns(META_ATTRS)].concat();
+    let edge_columns = [columns(EDGE_ATTRS), columns(INFO_ATTRS)].concat();
+    tx.execute_batch(&format!(
+        "CREATE TABLE packages (
+            id TEXT PRIMARY KEY,
+            {}
+        );
+        CREATE INDEX packages_name ON packages (name);
+
+        CREATE TABLE edges (
+            id TEXT PRIMARY KEY,
+            source TEXT NOT NULL REFERENCES packages (id),
+            target TEXT NOT NULL REFERENCES packages (id),
+            {}
+        );
+        CREATE INDEX edges_source ON edges (source);
+        CREATE INDEX edges_target ON edges (target);
+
+        CREATE TABLE features (
+            package_id TEXT NOT NULL REFERENCES packages (id),
+            name TEXT NOT NULL,
+            PRIMARY KEY (package_id, name)
+        );
+        -- What enabling a feature enables in turn, e.g. `dep:foo` or `bar/baz`
+        CREATE TABLE feature_enables (
+            package_id TEXT NOT NULL,
+            feature TEXT NOT NULL,
+            enables TEXT NOT NULL,
+            FOREIGN KEY (package_id, feature) REFERENCES features (package_id, name)
+        );
+        CREATE INDEX feature_enables_feature ON feature_enables (package_id, feature);
+
+        CREATE TABLE targets (
+            id INTEGER PRIMARY KEY,
+            package_id TEXT NOT NULL REFERENCES packages (id),
+            name TEXT NOT NULL,
+            src_path TEXT NOT NULL,
+            edition TEXT NOT NULL
+        );
+        CREATE INDEX targets_package_id ON targets (package_id);
+        CREATE TABLE target_kinds (
+            target_id INTEGER NOT NULL REFERENCES targets (id),
+            kind TEXT NOT NULL,
+            PRIMARY KEY (target_id, kind)
+        );
+        CREATE INDEX target_kinds_kind ON target_kinds (kind);
+
+        CREATE TABLE authors (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE
+        );
+        CREATE TABLE package_authors (
+            package_id TEXT NOT NULL REFERENCES packages (id),
+            author_id INTEGER NOT NULL REFERENCES authors (id),
+            PRIMARY KEY (package_id, author_id)
+        );
+        CREATE INDEX package_authors_author_id ON package_authors (author_id);",
+        package_columns.join(",\n            "),
+        edge_columns.join(",\n            "),
+    ))?;
+
+    // Statements borrow the transaction, so they have to be gone before committing it
+    {
+        let mut insert_package =
+            tx.prepare(&insert_statement("packages", 1 + package_columns.len()))?;
+        let mut insert_edge = tx.prepare(&insert_statement("edges", 3 + edge_columns.len()))?;
+        let mut insert_feature = tx.prepare("INSERT INTO features VALUES (?1, ?2)")?;
+        let mut insert_feature_enables =
+            tx.prepare("INSERT INTO feature_enables VALUES (?1, ?2, ?3)")?;
+        let mut insert_target = tx.prepare(
+            "INSERT INTO targets (package_id, name, src_path, edition) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        let mut insert_target_kind = tx.prepare("INSERT INTO target_kinds VALUES (?1, ?2)")?;
+        let mut insert_author = tx.prepare(
+            "INSERT INTO authors (name) VALUES (?1) \
+             ON CONFLICT DO UPDATE SET name = name RETURNING id",
+        )?;
+        let mut insert_package_author =
+            tx.prepare("INSERT OR IGNORE INTO package_authors VALUES (?1, ?2)")?;
+
+        for pkg in graph.node_weights() {
+            let meta = depmap.get(&pkg.id);
+            let row: Vec<_> = [Value::Text(pkg.id.clone())]
+                .into_iter()
+                .chain(NODE_ATTRS.iter().map(|(_, _, get)| sql_value(get(pkg))))
+                .chain(INFO_ATTRS.iter().map(|(_, _, get)| sql_value(get(&pkg.dep_info))))
+                .chain(
+                    META_ATTRS
+                        .iter()
+                        .map(|(_, _, get)| sql_value(meta.map_or(AttrValue::Null, *get))),
+                )
+                .collect();
+            insert_package.execute(rusqlite::params_from_iter(row))?;
+
+            let Some(meta) = meta else {
+                continue;
+            };
+            for (feature, enables) in &meta.features {
+                insert_feature.execute(params![pkg.id, feature])?;
+                for e in enables {
+                    insert_feature_enables.execute(params![pkg.id, feature, e])?;
+                }
+            }
+            for target in &meta.targets {
+                insert_target.execute(params![
+                    pkg.id,
+                    target.name,
+                    target.src_path.as_str(),
+                    target.edition.as_str()
+                ])?;
+                let target_id = tx.last_insert_rowid();
+                for kind in &target.kind {
+                    insert_target_kind.execute(params![target_id, kind.to_string()])?;
+                }
+            }
+            for author in &meta.authors {
+                let author_id: i64 = insert_author.query_row(params![author], |row| row.get(0))?;
+                insert_package_author.execute(params![pkg.id, author_id])?;
+            }
+        }
+
+        for dep in graph.edge_weights() {
+            let row: Vec<_> = [
+                Value::Text(dep.id.clone()),
+                Value::Text(dep.source.clone()),
+                Value::Text(dep.target.clone()),
+            ]
+            .into_iter()
+            .chain(EDGE_ATTRS.iter().map(|(_, _, get)| sql_value(get(dep))))
+            .chain(INFO_ATTRS.iter().map(|(_, _, get)| sql_value(get(&dep.inner))))
+            .collect();
+            insert_edge.execute(rusqlite::params_from_iter(row))?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+fn columns<T>(attrs: &[Attr<T>]) -> Vec<String> {
+    attrs.iter().map(|(name, ty, _)| format!("{name} {}", ty.sql_type())).collect()
+}
+
+fn insert_statement(table: &str, len: usize) -> String {
+    let placeholders: Vec<_> = (1..=len).map(|i| format!("?{i}")).collect();
+    format!("INSERT INTO {table} VALUES ({})", placeholders.join(", "))
+}
+
+fn sql_value(v: AttrValue) -> Value {
+    match v {
+        AttrValue::Bool(v) => Value::Integer(v.into()),
+        AttrValue::Int(v) => Value::Integer(v as i64),
+        AttrValue::Float(v) => Value::Real(v),
+        AttrValue::String(v) => Value::Text(v),
+        AttrValue::Null => Value::Null,
+    }
+}
@@ -0,0 +1,122 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::Context;
+
+use super::{
+    attrs::{AttrValue, EDGE_ATTRS, INFO_ATTRS, META_ATTRS, NODE_ATTRS},
+    replace_file,
+};
+use crate::graph::{DepGraph, DepMap};
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Separator {
+    Comma,
+    Tab,
+}
+
+impl Separator {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Comma => "csv",
+            Self::Tab => "tsv",
+        }
+    }
+
+    fn write_row(self, out: &mut dyn Write, fields: &[String]) -> io::Result<()> {
+        let fields: Vec<_> = fields.iter().map(|f| self.escape(f)).collect();
+        match self {
+            Self::Comma => writeln!(out, "{}", fields.join(",")),
+            Self::Tab => writeln!(out, "{}", fields.join("\t")),
+        }
+    }
+
+    fn escape(self, field: &str) -> String {
+        match self {
+            // RFC 4180
+            Self::Comma if field.contains([',', '"', '\n', '\r']) => {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            }
+            Self::Comma => field.to_owned(),
+            // TSV has no quoting, so separators within fields have to go
+            Self::Tab => field.replace(['\t', '\n', '\r'], " "),
+        }
+    }
+}
+
+/// Writes `nodes.<ext>` and `edges.<ext>` to `dir`
+pub(crate) fn write(
+    dir: &Path,
+    graph: &DepGraph,
+    depmap: &DepMap,
+    separator: Separator,
+) -> anyhow::Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let ext = separator.extension();
+    let nodes = dir.join(format!("nodes.{ext}"));
+    replace_file(&nodes, |tmp| write_table(tmp, |out| write_nodes(out, graph, depmap, separator)))?;
+    let edges = dir.join(format!("edges.{ext}"));
+    replace_file(&edges, |tmp| write_table(tmp, |out| write_edges(out, graph, separator)))?;
+
+    Ok(())
+}
+
+fn write_table(
+    path: &Path,
+    write: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+) -> anyhow::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    write(&mut out)?;
+    Ok(out.flush()?)
+}
+
+fn write_nodes(
+    out: &mut dyn Write,
+    graph: &DepGraph,
+    depmap: &DepMap,
+    separator: Separator,
+) -> io::Result<()> {
+    let header: Vec<_> = ["id"]
+        .into_iter()
+        .chain(NODE_ATTRS.iter().map(|(name, ..)| *name))
+        .chain(INFO_ATTRS.iter().map(|(name, ..)| *name))
+        .chain(META_ATTRS.iter().map(|(name, ..)| *name))
+        .map(str::to_owned)
+        .collect();
+    separator.write_row(out, &header)?;
+    for pkg in graph.node_weights() {
+        let meta = depmap.get(&pkg.id);
+        let row: Vec<_> = [pkg.id.clone()]
+            .into_iter()
+            .chain(NODE_ATTRS.iter().map(|(_, _, get)| get(pkg).to_string()))
+            .chain(INFO_ATTRS.iter().map(|(_, _, get)| get(&pkg.dep_info).to_string()))
+            .chain(
+                META_ATTRS.iter().map(|(_, _, get)| meta.map_or(AttrValue::Null, *get).to_string()),
+            )
+            .collect();
+        separator.write_row(out, &row)?;
+    }
+    Ok(())
+}
+
+fn write_edges(out: &mut dyn Write, graph: &DepGraph, separator: Separator) -> io::Result<()> {
+    let header: Vec<_> = ["id", "source", "target"]
+        .into_iter()
+        .chain(EDGE_ATTRS.iter().map(|(name, ..)| *name))
+        .chain(INFO_ATTRS.iter().map(|(name, ..)| *name))
+        .map(str::to_owned)
+        .collect();
+    separator.write_row(out, &header)?;
+    for dep in graph.edge_weights() {
+        let row: Vec<_> = [dep.id.clone(), dep.source.clone(), dep.target.clone()]
+            .into_iter()
+            .chain(EDGE_ATTRS.iter().map(|(_, _, get)| get(dep).to_string()))
+            .chain(INFO_ATTRS.iter().map(|(_, _, get)| get(&dep.inner).to_string()))
+            .collect();
+        separator.write_row(out, &row)?;
+    }
+    Ok(())
+}
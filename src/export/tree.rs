@@ -0,0 +1,117 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, Write},
+};
+
+use petgraph::{stable_graph::NodeIndex, visit::EdgeRef, Direction};
+
+use crate::{
+    dep_info::{BuildFlag, DepInfoInner, DepKind},
+    graph::DepGraph,
+    package::Package,
+    util::edge_labels,
+};
+
+/// Prints the graph as an indented tree like `cargo tree`, starting at the workspace members.
+///
+/// Packages that were already expanded further up are not expanded again, instead they refer back
+/// to their first occurrence by number.
+pub(crate) fn write(out: &mut dyn Write, graph: &DepGraph) -> io::Result<()> {
+    let mut versions = HashMap::<&str, Vec<String>>::new();
+    for pkg in graph.node_weights() {
+        versions.entry(&pkg.name).or_default().push(pkg.version.to_string());
+    }
+
+    let mut roots: Vec<_> = graph.node_indices().filter(|&idx| graph[idx].is_ws_member).collect();
+    roots.sort_by(|&a, &b| cmp_pkg(&graph[a], &graph[b]));
+
+    let mut printer = TreePrinter { graph, versions, refs: HashMap::new(), out };
+    for (i, root) in roots.into_iter().enumerate() {
+        if i > 0 {
+            writeln!(printer.out)?;
+        }
+        printer.print(root, &[], "")?;
+    }
+    Ok(())
+}
+
+struct TreePrinter<'a> {
+    graph: &'a DepGraph,
+    /// All versions of each package name in the graph
+    versions: HashMap<&'a str, Vec<String>>,
+    /// Reference numbers of packages whose dependencies were already printed
+    refs: HashMap<NodeIndex<u16>, usize>,
+    out: &'a mut dyn Write,
+}
+
+impl TreePrinter<'_> {
+    fn print(
+        &mut self,
+        idx: NodeIndex<u16>,
+        edges: &[DepInfoInner],
+        prefix: &str,
+    ) -> io::Result<()> {
+        let pkg = &self.graph[idx];
+        write!(self.out, "{} {}", pkg.name, pkg.version)?;
+
+        let mut annotations: Vec<_> = edge_labels(edges).into_iter().map(str::to_owned).collect();
+        // Most packages are only built for the target, don't clutter the output with that
+        if !pkg.is_ws_member && pkg.dep_info.kind != DepKind::NORMAL {
+            annotations.push(kind_annotation(pkg.dep_info.kind));
+        }
+        let others: Vec<_> = self.versions[pkg.name.as_str()]
+            .iter()
+            .filter(|v| **v != pkg.version.to_string())
+            .map(String::as_str)
+            .collect();
+        if !others.is_empty() {
+            annotations.push(format!("also {}", others.join(", ")));
+        }
+        if !annotations.is_empty() {
+            write!(self.out, " ({})", annotations.join("; "))?;
+        }
+
+        // Group parallel edges to the same dependency, e.g. a normal and a dev-dependency
+        let mut children = BTreeMap::<NodeIndex<u16>, Vec<DepInfoInner>>::new();
+        for edge in self.graph.edges_directed(idx, Direction::Outgoing) {
+            children.entry(edge.target()).or_default().push(edge.weight().inner);
+        }
+        if children.is_empty() {
+            return writeln!(self.out);
+        }
+
+        if let Some(n) = self.refs.get(&idx) {
+            return writeln!(self.out, " (see [{n}])");
+        }
+        let n = self.refs.len() + 1;
+        self.refs.insert(idx, n);
+        writeln!(self.out, " [{n}]")?;
+
+        let mut children: Vec<_> = children.into_iter().collect();
+        children.sort_by(|(a, _), (b, _)| cmp_pkg(&self.graph[*a], &self.graph[*b]));
+        let count = children.len();
+        for (i, (child, edges)) in children.into_iter().enumerate() {
+            let last = i + 1 == count;
+            write!(self.out, "{prefix}{}", if last { "└── " } else { "├── " })?;
+            let prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+            self.print(child, &edges, &prefix)?;
+        }
+        Ok(())
+    }
+}
+
+fn cmp_pkg(a: &Package, b: &Package) -> std::cmp::Ordering {
+    (&a.name, &a.version).cmp(&(&b.name, &b.version))
+}
+
+fn kind_annotation(kind: DepKind) -> String {
+    fn flag(flag: BuildFlag) -> &'static str {
+        match flag {
+            BuildFlag::Always => "always",
+            BuildFlag::Test => "test",
+            BuildFlag::Never => "never",
+        }
+    }
+
+    format!("host: {}, target: {}", flag(kind.host), flag(kind.target))
+}
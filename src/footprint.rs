@@ -0,0 +1,108 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, BufRead, BufReader},
+    ops::AddAssign,
+    path::Path,
+};
+
+use petgraph::visit::Dfs;
+use serde::Serialize;
+
+use crate::{
+    graph::{exclusive_deps, DepGraph, DepMap},
+    util::package_files,
+};
+
+/// Size of the source code of one or more packages
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub(crate) struct Footprint {
+    pub files: u64,
+    pub bytes: u64,
+    /// Lines in `.rs` files that are neither blank nor line comments
+    pub rust_lines: u64,
+}
+
+impl AddAssign for Footprint {
+    fn add_assign(&mut self, rhs: Self) {
+        self.files += rhs.files;
+        self.bytes += rhs.bytes;
+        self.rust_lines += rhs.rust_lines;
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub(crate) struct PackageFootprint {
+    /// The package on its own
+    pub own: Footprint,
+    /// The package along with the dependencies that are only reachable through it
+    pub exclusive: Footprint,
+    /// The package along with all of its transitive dependencies
+    pub total: Footprint,
+}
+
+/// Measures the source directory of every package and sums the results up over its dependencies
+pub(crate) fn set_footprints(graph: &mut DepGraph, depmap: &DepMap) {
+    let mut own = HashMap::new();
+    for idx in graph.node_indices() {
+        let pkg = &graph[idx];
+        // Feature nodes share the directory of their package
+        if pkg.feature.is_some() {
+            continue;
+        }
+        let Some(dir) = depmap.get(&pkg.id).and_then(|meta| meta.manifest_path.parent()) else {
+            continue;
+        };
+        match measure(dir.as_std_path()) {
+            Ok(footprint) => {
+                own.insert(idx, footprint);
+            }
+            Err(e) => eprintln!("Failed to measure the sources of {}: {e}", pkg.id),
+        }
+    }
+
+    let exclusive = exclusive_deps(graph);
+    for idx in graph.node_indices().collect::<Vec<_>>() {
+        if graph[idx].feature.is_some() {
+            continue;
+        }
+        let pkg_own = own.get(&idx).copied().unwrap_or_default();
+        let mut footprint =
+            PackageFootprint { own: pkg_own, exclusive: pkg_own, total: Footprint::default() };
+        for dep in exclusive.get(&idx).into_iter().flatten() {
+            footprint.exclusive += own.get(dep).copied().unwrap_or_default();
+        }
+        let mut dfs = Dfs::new(&*graph, idx);
+        while let Some(dep) = dfs.next(&*graph) {
+            footprint.total += own.get(&dep).copied().unwrap_or_default();
+        }
+        graph[idx].footprint = Some(footprint);
+    }
+}
+
+/// Sums up all files of the package in `dir`
+fn measure(dir: &Path) -> io::Result<Footprint> {
+    let mut res = Footprint::default();
+    for path in package_files(dir)? {
+        res.files += 1;
+        res.bytes += fs::metadata(&path)?.len();
+        if path.extension().is_some_and(|ext| ext == "rs") {
+            res.rust_lines += rust_lines(&path)?;
+        }
+    }
+    Ok(res)
+}
+
+fn rust_lines(path: &Path) -> io::Result<u64> {
+    let mut count = 0;
+    for line in BufReader::new(fs::File::open(path)?).split(b'\n') {
+        // Source files are not necessarily valid UTF-8, e.g. test data
+        let line = line?;
+        let line = String::from_utf8_lossy(&line);
+        let line = line.trim();
+        if !line.is_empty() && !line.starts_with("//") {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
@@ -1,23 +1,70 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{hash_map::Entry, HashMap, HashSet, VecDeque};
 
 use petgraph::{
-    algo::all_simple_paths,
+    algo::{dominators, has_path_connecting},
+    graph::DiGraph,
     stable_graph::{NodeIndex, StableDiGraph},
+    visit::{Dfs, EdgeRef, NodeFiltered, Walker},
     Direction,
 };
 
 use crate::{
     dep_info::{DepInfo, DepInfoInner},
     package::Package,
+    platform::TargetCfg,
 };
 
 mod build;
+mod features;
 
 pub(crate) use build::get_dep_graph;
 
 pub(crate) type DepGraph = StableDiGraph<Package, DepInfo, u16>;
 pub(crate) type DepMap = HashMap<String, cargo_metadata::Package>;
 
+/// Removes dependencies that don't apply to any of `platforms`, and records which of them each
+/// remaining package is built for. Like cargo, dependencies that are built for the host, i.e.
+/// build-dependencies, proc-macros and everything they depend on, are evaluated against `host`.
+pub(crate) fn apply_platforms(
+    graph: &mut DepGraph,
+    depmap: &mut DepMap,
+    platforms: &[TargetCfg],
+    host: &TargetCfg,
+) {
+    let roots: Vec<_> = graph.node_indices().filter(|&idx| graph[idx].is_ws_member).collect();
+    let mut applicable = HashSet::new();
+    for target in platforms {
+        // Packages are visited once per target they are built for, either `target` or `host`
+        let mut visited = HashSet::new();
+        let mut queue: VecDeque<_> = roots.iter().map(|&idx| (idx, false)).collect();
+        while let Some((idx, on_host)) = queue.pop_front() {
+            if !visited.insert((idx, on_host)) {
+                continue;
+            }
+            if !graph[idx].platforms.contains(&target.triple) {
+                graph[idx].platforms.push(target.triple.clone());
+            }
+            for edge in graph.edges_directed(idx, Direction::Outgoing) {
+                let dep_on_host = on_host || edge.weight().inner.kind.is_host_only();
+                let cfg = if dep_on_host { host } else { target };
+                if edge.weight().platform.as_ref().is_none_or(|p| cfg.matches(p)) {
+                    applicable.insert(edge.id());
+                    queue.push_back((edge.target(), dep_on_host));
+                }
+            }
+        }
+    }
+
+    graph.retain_edges(|_, edge| applicable.contains(&edge));
+    graph.retain_nodes(|g, idx| {
+        let keep = !g[idx].platforms.is_empty();
+        if !keep {
+            depmap.remove(&g[idx].id);
+        }
+        keep
+    });
+}
+
 pub(crate) fn update_dep_info(graph: &mut DepGraph) {
     for idx in graph.node_indices().collect::<Vec<_>>() {
         // We're only mutating nodes, not adding or deleting them, so we can safely use the indices
@@ -140,10 +187,24 @@ pub(crate) fn dedup_transitive_deps(graph: &mut DepGraph) {
             continue;
         }
 
+        // Features are not dependencies of their own, only edges between packages are deduplicated
+        // and only paths through other packages count
+        if graph[idx].feature.is_some() {
+            continue;
+        }
+
         let mut outgoing = graph.neighbors_directed(idx, Direction::Outgoing).detach();
         while let Some((edge_idx, node_idx)) = outgoing.next(graph) {
-            let any_paths =
-                all_simple_paths::<Vec<_>, _>(&*graph, idx, node_idx, 1, None).next().is_some();
+            // Any simple path with an intermediate package, i.e. one that goes through another
+            // dependency without coming back to this package
+            let is_package = |n: NodeIndex<u16>| graph[n].feature.is_none();
+            let others = NodeFiltered::from_fn(&*graph, |n| n != idx && is_package(n));
+            let any_paths = is_package(node_idx)
+                && graph.neighbors_directed(idx, Direction::Outgoing).any(|dep| {
+                    dep != node_idx
+                        && is_package(dep)
+                        && has_path_connecting(&others, dep, node_idx, None)
+                });
 
             if any_paths {
                 graph.remove_edge(edge_idx);
@@ -151,3 +212,101 @@ pub(crate) fn dedup_transitive_deps(graph: &mut DepGraph) {
         }
     }
 }
+
+/// Finds packages by `name`, `name@version` or their id (`name version`)
+pub(crate) fn find_packages(graph: &DepGraph, spec: &str) -> Vec<NodeIndex<u16>> {
+    let (name, version) = match spec.split_once(['@', ' ']) {
+        Some((name, version)) => (name, Some(version)),
+        None => (spec, None),
+    };
+    graph
+        .node_indices()
+        .filter(|&idx| {
+            let pkg = &graph[idx];
+            pkg.id == spec
+                || pkg.name == name && version.is_none_or(|v| pkg.version.to_string() == v)
+        })
+        .collect()
+}
+
+/// Finds the shortest dependency chain from any workspace member to the package at `idx`
+pub(crate) fn shortest_chain(graph: &DepGraph, idx: NodeIndex<u16>) -> Option<Vec<NodeIndex<u16>>> {
+    // Breadth-first search over the reverse graph, remembering where each node was reached from
+    let mut parents = HashMap::from([(idx, idx)]);
+    let mut queue = VecDeque::from([idx]);
+    while let Some(current) = queue.pop_front() {
+        if graph[current].is_ws_member {
+            let mut chain = vec![current];
+            let mut node = current;
+            while node != idx {
+                node = parents[&node];
+                chain.push(node);
+            }
+            return Some(chain);
+        }
+        for parent in graph.neighbors_directed(current, Direction::Incoming) {
+            if let Entry::Vacant(e) = parents.entry(parent) {
+                e.insert(current);
+                queue.push_back(parent);
+            }
+        }
+    }
+    None
+}
+
+/// Maps every package to its exclusive transitive dependencies.
+///
+/// A dependency is exclusive to a package if every chain from the workspace members to it passes
+/// through that package, i.e. if the package dominates it. Removing the package would thus remove
+/// all of its exclusive dependencies as well. Feature nodes are left out.
+pub(crate) fn exclusive_deps(graph: &DepGraph) -> HashMap<NodeIndex<u16>, Vec<NodeIndex<u16>>> {
+    // Dominators need a single root, so put a virtual node above all workspace members
+    let mut dom_graph = DiGraph::<NodeIndex<u16>, ()>::new();
+    let root = dom_graph.add_node(NodeIndex::end());
+    let dom_indices: HashMap<_, _> = graph
+        .node_indices()
+        .filter(|&idx| graph[idx].feature.is_none())
+        .map(|idx| (idx, dom_graph.add_node(idx)))
+        .collect();
+    for &idx in dom_indices.keys() {
+        if graph[idx].is_ws_member {
+            dom_graph.add_edge(root, dom_indices[&idx], ());
+        }
+    }
+    for edge in graph.edge_indices() {
+        let (source, target) = graph.edge_endpoints(edge).unwrap();
+        if let (Some(&source), Some(&target)) = (dom_indices.get(&source), dom_indices.get(&target))
+        {
+            dom_graph.update_edge(source, target, ());
+        }
+    }
+    let dominators = dominators::simple_fast(&dom_graph, root);
+
+    let mut exclusive = HashMap::<NodeIndex<u16>, Vec<NodeIndex<u16>>>::new();
+    for (&idx, &dom_idx) in &dom_indices {
+        let Some(strict_dominators) = dominators.strict_dominators(dom_idx) else {
+            continue;
+        };
+        for dominator in strict_dominators.filter(|&d| d != root) {
+            exclusive.entry(dom_graph[dominator]).or_default().push(idx);
+        }
+    }
+    exclusive
+}
+
+/// Sets the number of exclusive and shared transitive dependencies of every package, see
+/// [`exclusive_deps`]
+pub(crate) fn set_dependency_counts(graph: &mut DepGraph) {
+    let exclusive = exclusive_deps(graph);
+    let packages: Vec<_> =
+        graph.node_indices().filter(|&idx| graph[idx].feature.is_none()).collect();
+    for idx in packages {
+        // The package itself is not a dependency of its own
+        let package_graph = NodeFiltered::from_fn(&*graph, |n| graph[n].feature.is_none());
+        let reachable = Dfs::new(&package_graph, idx).iter(&package_graph).count() as u16 - 1;
+        let exclusive = exclusive.get(&idx).map_or(0, |deps| deps.len() as u16);
+        let pkg = &mut graph[idx];
+        pkg.exclusive_deps = exclusive;
+        pkg.shared_deps = reachable - exclusive;
+    }
+}
@@ -6,11 +6,12 @@ use indicatif::{ProgressBar, ProgressStyle};
 use nanoid::nanoid;
 use petgraph::prelude::NodeIndex;
 
-use super::{DepGraph, DepMap};
+use super::{apply_platforms, features::add_feature_nodes, DepGraph, DepMap};
 use crate::{
     cli::Config,
     dep_info::{DepInfo, DepInfoInner, DepKind},
     package::Package,
+    platform::TargetCfg,
     util::is_proc_macro,
 };
 
@@ -78,6 +79,7 @@ pub(crate) fn get_dep_graph(
             .iter()
             .find(|n| n.id == pkg_id)
             .context("package not found in resolve")?;
+        graph[parent_idx].features = resolve_node.features.clone();
 
         for dep in &resolve_node.deps {
             bar.inc(1);
@@ -138,15 +140,15 @@ pub(crate) fn get_dep_graph(
                         && d.target.as_ref().map(|t| t.to_string())
                             == info.target.as_ref().map(|t| t.to_string())
                 });
-                let is_optional = match extra {
-                    Some(dep) => dep.optional,
+                let (is_optional, features, uses_default_features) = match extra {
+                    Some(dep) => (dep.optional, dep.features.clone(), dep.uses_default_features),
                     None => {
                         eprintln!(
                             "dependency {} of {} not found in packages \
                              => dependencies, this should never happen!",
                             dep_crate_name, pkg.name,
                         );
-                        false
+                        (false, Vec::new(), true)
                     }
                 };
 
@@ -177,6 +179,11 @@ pub(crate) fn get_dep_graph(
                         source: graph[parent_idx].id.clone(),
                         target: graph[child_idx].id.clone(),
                         edge_no: *multiplicity,
+                        features,
+                        uses_default_features,
+                        platform: info.target.clone(),
+                        diff: None,
+                        in_cycle: false,
                         inner,
                     },
                 );
@@ -187,6 +194,21 @@ pub(crate) fn get_dep_graph(
 
     bar.finish();
 
+    if !config.platforms.is_empty() {
+        let platforms = config
+            .platforms
+            .iter()
+            .map(|triple| TargetCfg::new(triple))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let host = TargetCfg::host().context("Failed to determine the host platform")?;
+        apply_platforms(&mut graph, &mut depmap, &platforms, &host);
+        // Packages that apply to none of the platforms were removed along with their indices
+        node_indices.retain(|_, &mut idx| graph.contains_node(idx));
+    }
+    if config.feature_graph {
+        add_feature_nodes(&mut graph, &depmap, &resolve, &node_indices);
+    }
+
     Ok((graph, depmap))
 }
 
@@ -199,3 +221,41 @@ pub(crate) fn skip_dep(config: &Config, info: &cargo_metadata::DepKindInfo) -> b
         || (!config.dev_deps && info.kind == MetaDepKind::Development)
         || (!config.target_deps && info.target.is_some())
 }
+
+#[cfg(test)]
+mod tests {
+    use cargo_metadata::MetadataCommand;
+
+    use super::*;
+    use crate::cli::parse_args;
+
+    /// Builds the graph of this repository with the given options
+    fn own_graph(args: &[&str]) -> DepGraph {
+        let config = parse_args(["cargo", "visualize", "--offline"].iter().chain(args));
+        let metadata = MetadataCommand::new()
+            .manifest_path(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"))
+            .other_options(vec!["--offline".to_owned()])
+            .exec()
+            .unwrap();
+        get_dep_graph(metadata, &config).unwrap().0
+    }
+
+    /// Every feature node must belong to a package that is still in the graph
+    fn assert_features_belong_to_packages(graph: &DepGraph) {
+        for pkg in graph.node_weights().filter(|pkg| pkg.feature.is_some()) {
+            let (package_id, _) = pkg.id.split_once(" +").unwrap();
+            let package = graph.node_weights().find(|p| p.id == package_id);
+            assert!(package.is_some_and(|p| p.feature.is_none()), "orphaned feature {}", pkg.id);
+            assert!(pkg.name.starts_with(&format!("{}/", package.unwrap().name)));
+        }
+    }
+
+    #[test]
+    fn feature_graph_with_platforms() {
+        for platform in ["x86_64-unknown-linux-gnu", "x86_64-pc-windows-msvc"] {
+            let graph = own_graph(&["--platform", platform, "--feature-graph"]);
+            assert!(graph.node_weights().any(|pkg| pkg.feature.is_some()));
+            assert_features_belong_to_packages(&graph);
+        }
+    }
+}
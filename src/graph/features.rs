@@ -0,0 +1,250 @@
+use std::collections::{HashMap, HashSet};
+
+use cargo_metadata::{DependencyKind as MetaDepKind, PackageId, Resolve};
+use nanoid::nanoid;
+use petgraph::{
+    algo::has_path_connecting,
+    prelude::{EdgeIndex, NodeIndex},
+    visit::EdgeRef,
+    Direction,
+};
+
+use super::{DepGraph, DepMap};
+use crate::dep_info::{DepInfo, DepInfoInner};
+
+/// Adds a node for every enabled feature of every package in the graph.
+///
+/// Dependents are linked to the features they request, features are linked to the features and
+/// optional dependencies they enable in turn, and finally to the package they belong to. Edges
+/// between packages are kept, so package-level information stays the same.
+pub(super) fn add_feature_nodes(
+    graph: &mut DepGraph,
+    depmap: &DepMap,
+    resolve: &Resolve,
+    node_indices: &HashMap<PackageId, NodeIndex<u16>>,
+) {
+    let package_edges: Vec<_> = graph.edge_indices().collect();
+
+    let mut features = HashMap::<(NodeIndex<u16>, &str), NodeIndex<u16>>::new();
+    for node in &resolve.nodes {
+        let Some(&pkg_idx) = node_indices.get(&node.id) else {
+            continue;
+        };
+        for feature in &node.features {
+            let pkg = &graph[pkg_idx];
+            let mut weight = pkg.clone();
+            weight.id = format!("{} +{feature}", pkg.id);
+            weight.name = format!("{}/{feature}", pkg.name);
+            weight.is_ws_member = false;
+            weight.is_proc_macro = false;
+            weight.feature = Some(feature.clone());
+
+            let is_ws_member = pkg.is_ws_member;
+            let idx = graph.add_node(weight);
+            features.insert((pkg_idx, feature), idx);
+            // Features of workspace members are enabled from the command line
+            if is_ws_member {
+                add_edge(graph, pkg_idx, idx, DepInfoInner::default());
+            }
+        }
+    }
+
+    // Dependents request features of their dependencies
+    for edge in package_edges {
+        let (source, target) = graph.edge_endpoints(edge).unwrap();
+        let info = graph[edge].inner;
+        let kind = if info.is_dev {
+            MetaDepKind::Development
+        } else if info.is_build {
+            MetaDepKind::Build
+        } else {
+            MetaDepKind::Normal
+        };
+        let dep_pkg = &graph[target];
+        let mut requested = HashSet::new();
+        for dep in &depmap[&graph[source].id].dependencies {
+            if dep.name != dep_pkg.name || dep.kind != kind || !dep.req.matches(&dep_pkg.version) {
+                continue;
+            }
+            requested.extend(dep.features.iter().map(String::as_str));
+            if dep.uses_default_features {
+                requested.insert("default");
+            }
+        }
+        for feature in requested {
+            if let Some(&idx) = features.get(&(target, feature)) {
+                add_edge_acyclic(graph, source, idx, info);
+            }
+        }
+    }
+
+    // Features enable other features of the same package, optional dependencies and features of
+    // dependencies. Edges within a package depend on how the feature itself is pulled in, they
+    // are filled in below.
+    let mut derived = HashSet::new();
+    for (&(pkg_idx, feature), &idx) in &features {
+        let meta = &depmap[&graph[pkg_idx].id];
+        let Some(enables) = meta.features.get(feature) else {
+            continue;
+        };
+        let mut targets = Vec::new();
+        for value in enables {
+            if let Some(dep_name) = value.strip_prefix("dep:") {
+                targets.extend(dependencies(graph, depmap, pkg_idx, dep_name));
+            } else if let Some((dep_name, dep_feature)) = value.split_once('/') {
+                let weak = dep_name.ends_with('?');
+                let dep_name = dep_name.trim_end_matches('?');
+                for (dep_idx, info) in dependencies(graph, depmap, pkg_idx, dep_name) {
+                    if let Some(&dep_feature_idx) = features.get(&(dep_idx, dep_feature)) {
+                        targets.push((dep_feature_idx, info));
+                    }
+                }
+                // `dep/feature` also enables the optional dependency through its implicit feature
+                if !weak {
+                    if let Some(&implicit) = features.get(&(pkg_idx, dep_name)) {
+                        derived.insert((idx, implicit));
+                    }
+                }
+            } else if let Some(&other) = features.get(&(pkg_idx, value.as_str())) {
+                derived.insert((idx, other));
+            }
+        }
+        for (target, info) in targets {
+            add_edge_acyclic(graph, idx, target, info);
+        }
+        if !graph[pkg_idx].is_ws_member {
+            derived.insert((idx, pkg_idx));
+        }
+    }
+    let derived: HashSet<_> = derived
+        .into_iter()
+        .filter_map(|(source, target)| {
+            add_edge_acyclic(graph, source, target, DepInfoInner::default())
+        })
+        .collect();
+
+    // Features that are only enabled through dependencies which are not part of the graph
+    loop {
+        let orphans: Vec<_> = features
+            .values()
+            .copied()
+            .filter(|&idx| {
+                graph.contains_node(idx)
+                    && graph.neighbors_directed(idx, Direction::Incoming).next().is_none()
+            })
+            .collect();
+        if orphans.is_empty() {
+            break;
+        }
+        for idx in orphans {
+            graph.remove_node(idx);
+        }
+    }
+
+    let mut memo = HashMap::new();
+    for &edge in &derived {
+        let Some((source, _)) = graph.edge_endpoints(edge) else {
+            continue;
+        };
+        if let Some(mut info) = feature_info(graph, &derived, &mut memo, source) {
+            info.is_optional_direct = false;
+            graph[edge].inner = info;
+        }
+    }
+}
+
+/// Packages in the graph that `pkg_idx` refers to as `dep_name` in its manifest, along with the
+/// info of the edge leading to them
+fn dependencies(
+    graph: &DepGraph,
+    depmap: &DepMap,
+    pkg_idx: NodeIndex<u16>,
+    dep_name: &str,
+) -> Vec<(NodeIndex<u16>, DepInfoInner)> {
+    let meta = &depmap[&graph[pkg_idx].id];
+    let mut res: Vec<(NodeIndex<u16>, DepInfoInner)> = Vec::new();
+    for dep in &meta.dependencies {
+        if dep.rename.as_deref().unwrap_or(&dep.name) != dep_name {
+            continue;
+        }
+        for edge in graph.edges_directed(pkg_idx, Direction::Outgoing) {
+            let child = &graph[edge.target()];
+            if child.feature.is_none()
+                && child.name == dep.name
+                && dep.req.matches(&child.version)
+                && !res.iter().any(|(idx, _)| *idx == edge.target())
+            {
+                res.push((edge.target(), edge.weight().inner));
+            }
+        }
+    }
+    res
+}
+
+/// Combined info of all edges leading to a feature node, like `update_dep_info` does for packages
+fn feature_info(
+    graph: &DepGraph,
+    derived: &HashSet<EdgeIndex<u16>>,
+    memo: &mut HashMap<NodeIndex<u16>, Option<DepInfoInner>>,
+    idx: NodeIndex<u16>,
+) -> Option<DepInfoInner> {
+    if let Some(info) = memo.get(&idx) {
+        return *info;
+    }
+
+    let mut res: Option<DepInfoInner> = None;
+    for edge in graph.edges_directed(idx, Direction::Incoming) {
+        let edge_info = if derived.contains(&edge.id()) {
+            feature_info(graph, derived, memo, edge.source())
+        } else {
+            Some(edge.weight().inner)
+        };
+        let Some(edge_info) = edge_info else {
+            continue;
+        };
+        if let Some(i) = &mut res {
+            i.is_target_dep &= edge_info.is_target_dep;
+            i.is_optional &= edge_info.is_optional;
+            i.combine_incoming(edge_info);
+            i.kind.combine_incoming(edge_info.kind);
+        } else {
+            res = Some(edge_info);
+        }
+    }
+    memo.insert(idx, res);
+    res
+}
+
+/// Adds an edge unless it would close a cycle, which the rest of the graph logic can't deal with
+fn add_edge_acyclic(
+    graph: &mut DepGraph,
+    source: NodeIndex<u16>,
+    target: NodeIndex<u16>,
+    inner: DepInfoInner,
+) -> Option<EdgeIndex<u16>> {
+    if source == target || has_path_connecting(&*graph, target, source, None) {
+        return None;
+    }
+    Some(add_edge(graph, source, target, inner))
+}
+
+fn add_edge(
+    graph: &mut DepGraph,
+    source: NodeIndex<u16>,
+    target: NodeIndex<u16>,
+    inner: DepInfoInner,
+) -> EdgeIndex<u16> {
+    let weight = DepInfo {
+        id: nanoid!(),
+        source: graph[source].id.clone(),
+        target: graph[target].id.clone(),
+        edge_no: graph.edges_connecting(source, target).count() as u32,
+        features: Vec::new(),
+        uses_default_features: false,
+        platform: None,
+        diff: None,
+        in_cycle: false,
+        inner,
+    };
+    graph.add_edge(source, target, weight)
+}
@@ -1,6 +1,7 @@
 use std::{
-    iter,
+    io, iter,
     net::{Ipv4Addr, SocketAddrV4},
+    path::Path,
     sync::Arc,
 };
 
@@ -9,7 +10,7 @@ use axum::{
     routing::{get, post},
     Router,
 };
-use cargo_metadata::MetadataCommand;
+use cargo_metadata::{camino::Utf8PathBuf, MetadataCommand};
 use cfg_if::cfg_if;
 use console::style;
 use graph::{DepGraph, DepMap};
@@ -29,6 +30,25 @@ mod util;
 
 // Command-line parsing
 mod cli;
+// Evaluating target-specific dependencies for target triples
+mod platform;
+
+// Reading the registry index cached by cargo
+mod registry;
+
+// Comparing the dependency graphs of two revisions
+mod diff;
+
+// Measuring the sources of packages on disk
+mod footprint;
+
+// Importing compile times from cargo's timing reports
+mod timings;
+
+// Writers for exporting the graph to files instead of serving it
+mod export;
+// Analyses of the graph, served by the web service or printed to the terminal
+mod report;
 
 // Embedded assets
 #[cfg(embed)]
@@ -38,9 +58,19 @@ mod assets;
 mod routes;
 
 use self::{
-    cli::parse_options,
+    cli::{parse_options, Config},
+    export::{export, ExportFormat},
     graph::{
-        dedup_transitive_deps, get_dep_graph, remove_deps, remove_irrelevant_deps, update_dep_info,
+        dedup_transitive_deps, get_dep_graph, remove_deps, remove_irrelevant_deps,
+        set_dependency_counts, update_dep_info,
+    },
+    report::{
+        advisories::{set_advisories, AdvisoryDb},
+        cycles::set_cycle_edges,
+        licenses::{set_license_verdicts, LicensePolicy},
+        metrics::set_metrics,
+        outdated::set_registry_status,
+        unsafe_code::set_unsafe_counts,
     },
     util::set_name_stats,
 };
@@ -54,48 +84,96 @@ struct AppState {
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> anyhow::Result<()> {
     let config = parse_options();
-    let mut cmd = MetadataCommand::new();
-
-    if let Some(path) = &config.manifest_path {
-        cmd.manifest_path(path);
+    // Temporary checkouts of diffed revisions are removed when dropped at the end of `main`
+    let (mut graph, depmap, workspace_root, _checkouts) = if let Some((old, new)) = &config.diff {
+        let (graph, depmap, workspace_root, checkouts) = diff::diff_graphs(&config, old, new)?;
+        (graph, depmap, workspace_root, Some(checkouts))
+    } else {
+        let (graph, depmap, workspace_root) =
+            build_graph(&config, config.manifest_path.as_deref(), false)?;
+        (graph, depmap, workspace_root, None)
+    };
+    set_name_stats(&mut graph);
+    set_dependency_counts(&mut graph);
+    set_cycle_edges(&mut graph);
+    // Betweenness takes a while on large graphs, so metrics are only computed where they are shown
+    let prints_other_report = config.why.is_some()
+        || config.check_licenses
+        || config.cycles
+        || config.advisories
+        || config.outdated
+        || config.unsafe_report
+        || config.duplicates;
+    let needs_metrics = config.metrics
+        || config.export.map_or(!prints_other_report, ExportFormat::includes_metrics);
+    if needs_metrics {
+        set_metrics(&mut graph);
     }
-
-    let mut other_options = Vec::new();
-    other_options.extend(config.features.iter().flat_map(|f| cli_args("--features", f)));
-    if config.all_features {
-        other_options.push("--all-features".into());
+    if config.footprint {
+        eprintln!("{} Measuring package sources...", style("[2/3]").bold().dim(),);
+        footprint::set_footprints(&mut graph, &depmap);
     }
-    if config.no_default_features {
-        other_options.push("--no-default-features".into());
+    if config.scan_unsafe {
+        eprintln!("{} Scanning package sources for unsafe code...", style("[2/3]").bold().dim(),);
+        set_unsafe_counts(&mut graph, &depmap);
     }
-    other_options
-        .extend(config.filter_platform.iter().flat_map(|p| cli_args("--filter-platform", p)));
-    if config.frozen {
-        other_options.push("--frozen".into());
+    if let Some(path) = &config.timings {
+        let units = timings::load(Path::new(path))?;
+        timings::set_build_times(&mut graph, &depmap, &units);
     }
-    if config.locked {
-        other_options.push("--locked".into());
+    if let Some(path) = &config.license_policy {
+        let policy = LicensePolicy::load(Path::new(path))?;
+        set_license_verdicts(&mut graph, &depmap, &policy);
     }
-    if config.offline {
-        other_options.push("--offline".into());
+    if let Some(path) = &config.advisory_db {
+        let db = AdvisoryDb::load(Path::new(path))?;
+        set_advisories(&mut graph, &depmap, &db);
+    }
+    if config.registry_versions {
+        set_registry_status(&mut graph, &depmap);
     }
-    other_options.extend(config.unstable_flags.iter().flat_map(|f| cli_args("-Z", f)));
 
-    let metadata = cmd.other_options(other_options).exec()?;
-    eprintln!("{} Building dependency graph...", style("[1/3]").bold().dim(),);
-    let (mut graph, depmap) = get_dep_graph(metadata, &config)?;
-    eprintln!("{} Updating dependency info...", style("[2/3]").bold().dim(),);
-    update_dep_info(&mut graph);
-    if !config.focus.is_empty() {
-        remove_irrelevant_deps(&mut graph, &config.focus);
+    if let Some(spec) = &config.why {
+        eprintln!("{} Looking for dependency chains...", style("[3/3]").bold().dim(),);
+        return report::why::print(&mut io::stdout().lock(), &graph, spec);
     }
-    if !config.hide.is_empty() {
-        remove_deps(&mut graph, &config.hide);
+    if config.check_licenses {
+        eprintln!("{} Checking licenses...", style("[3/3]").bold().dim(),);
+        return report::licenses::check(&mut io::stdout().lock(), &graph, &depmap);
     }
-    if config.dedup_transitive_deps {
-        dedup_transitive_deps(&mut graph);
+    if config.cycles {
+        eprintln!("{} Looking for cycles...", style("[3/3]").bold().dim(),);
+        report::cycles::print(&mut io::stdout().lock(), &graph)?;
+        return Ok(());
+    }
+    if config.metrics {
+        eprintln!("{} Computing graph metrics...", style("[3/3]").bold().dim(),);
+        report::metrics::print(&mut io::stdout().lock(), &graph)?;
+        return Ok(());
+    }
+    if config.advisories {
+        eprintln!("{} Matching advisories...", style("[3/3]").bold().dim(),);
+        return report::advisories::print(&mut io::stdout().lock(), &graph);
+    }
+    if config.outdated {
+        eprintln!("{} Looking for outdated packages...", style("[3/3]").bold().dim(),);
+        report::outdated::print(&mut io::stdout().lock(), &graph)?;
+        return Ok(());
+    }
+    if config.unsafe_report {
+        eprintln!("{} Looking for unsafe code...", style("[3/3]").bold().dim(),);
+        report::unsafe_code::print(&mut io::stdout().lock(), &graph)?;
+        return Ok(());
+    }
+    if config.duplicates {
+        eprintln!("{} Looking for duplicate packages...", style("[3/3]").bold().dim(),);
+        report::duplicates::print(&mut io::stdout().lock(), &graph, &depmap)?;
+        return Ok(());
+    }
+    if let Some(format) = config.export {
+        eprintln!("{} Exporting dependency graph...", style("[3/3]").bold().dim(),);
+        return export(&graph, &depmap, &workspace_root, format, config.output.as_deref());
     }
-    set_name_stats(&mut graph);
 
     cfg_if! {
         if #[cfg(debug_assertions)] {
@@ -111,6 +189,14 @@ async fn main() -> anyhow::Result<()> {
         .route("/nodes", get(routes::handler_nodes))
         .route("/edges", get(routes::handler_edges))
         .route("/graph", get(routes::handler_graph))
+        .route("/why/{id}", get(routes::handler_why))
+        .route("/duplicates", get(routes::handler_duplicates))
+        .route("/licenses", get(routes::handler_licenses))
+        .route("/cycles", get(routes::handler_cycles))
+        .route("/metrics", get(routes::handler_metrics))
+        .route("/advisories", get(routes::handler_advisories))
+        .route("/outdated", get(routes::handler_outdated))
+        .route("/unsafe", get(routes::handler_unsafe))
         .layer(cors)
         .with_state(AppState { graph: Arc::new(graph), depmap: Arc::new(depmap) });
 
@@ -160,6 +246,59 @@ async fn main() -> anyhow::Result<()> {
     return Ok(());
 }
 
+/// Runs `cargo metadata` for the workspace of `manifest_path` and builds its dependency graph,
+/// with `--locked` if `locked` is set
+fn build_graph(
+    config: &Config,
+    manifest_path: Option<&str>,
+    locked: bool,
+) -> anyhow::Result<(DepGraph, DepMap, Utf8PathBuf)> {
+    let mut cmd = MetadataCommand::new();
+
+    if let Some(path) = manifest_path {
+        cmd.manifest_path(path);
+    }
+
+    let mut other_options = Vec::new();
+    other_options.extend(config.features.iter().flat_map(|f| cli_args("--features", f)));
+    if config.all_features {
+        other_options.push("--all-features".into());
+    }
+    if config.no_default_features {
+        other_options.push("--no-default-features".into());
+    }
+    other_options
+        .extend(config.filter_platform.iter().flat_map(|p| cli_args("--filter-platform", p)));
+    if config.frozen {
+        other_options.push("--frozen".into());
+    }
+    if config.locked || locked {
+        other_options.push("--locked".into());
+    }
+    if config.offline {
+        other_options.push("--offline".into());
+    }
+    other_options.extend(config.unstable_flags.iter().flat_map(|f| cli_args("-Z", f)));
+
+    let metadata = cmd.other_options(other_options).exec()?;
+    let workspace_root = metadata.workspace_root.clone();
+    eprintln!("{} Building dependency graph...", style("[1/3]").bold().dim(),);
+    let (mut graph, depmap) = get_dep_graph(metadata, config)?;
+    eprintln!("{} Updating dependency info...", style("[2/3]").bold().dim(),);
+    update_dep_info(&mut graph);
+    if !config.focus.is_empty() {
+        remove_irrelevant_deps(&mut graph, &config.focus);
+    }
+    if !config.hide.is_empty() {
+        remove_deps(&mut graph, &config.hide);
+    }
+    if config.dedup_transitive_deps {
+        dedup_transitive_deps(&mut graph);
+    }
+
+    Ok((graph, depmap, workspace_root))
+}
+
 fn cli_args(opt_name: &str, val: &str) -> impl Iterator<Item = String> {
     iter::once(opt_name.into()).chain(iter::once(val.into()))
 }
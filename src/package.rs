@@ -11,6 +11,12 @@ use serde::{Serialize, Serializer};
 
 use crate::{
     dep_info::{DepInfoInner, DepKind},
+    diff::DiffStatus,
+    footprint::PackageFootprint,
+    report::{
+        advisories::Advisory, licenses::Verdict, metrics::NodeMetrics, outdated::RegistryStatus,
+        unsafe_code::PackageUnsafe,
+    },
     util::is_proc_macro,
 };
 
@@ -23,9 +29,38 @@ pub(crate) struct Package {
     pub dep_info: DepInfoInner,
     pub is_ws_member: bool,
     pub is_proc_macro: bool,
+    /// Name of the feature, if this node represents a feature of the package
+    pub feature: Option<String>,
+    /// Features enabled for the package by the resolver
+    pub features: Vec<String>,
+    /// Target triples given with `--platform` that the package is built for
+    pub platforms: Vec<String>,
+    /// How the package changed, when comparing two revisions
+    pub diff: Option<DiffStatus>,
+    /// Version the package was upgraded or downgraded from
+    pub previous_version: Option<Version>,
+    /// Verdict of the license policy given with `--license-policy`
+    pub license_verdict: Option<Verdict>,
+    /// RustSec advisories that apply to the package, given with `--advisory-db`
+    pub advisories: Vec<Advisory>,
+    /// Newer and yanked releases in the registry index, looked up with `--registry-versions`
+    pub registry: Option<RegistryStatus>,
+    /// Size of the sources on disk, measured with `--footprint`
+    pub footprint: Option<PackageFootprint>,
+    /// Seconds it took to compile all units of the package, imported with `--timings`
+    pub build_time: Option<f64>,
+    /// Uses of `unsafe` in the sources, scanned with `--scan-unsafe`
+    pub unsafe_code: Option<PackageUnsafe>,
+    /// Whether the package is on the longest chain of dependencies to compile
+    pub on_critical_path: bool,
 
     #[serde(serialize_with = "serialize_name_uses")]
     pub name_uses: Option<Arc<AtomicU16>>,
+    /// Number of transitive dependencies that are only reachable through this package
+    pub exclusive_deps: u16,
+    /// Number of transitive dependencies that are also reachable without this package
+    pub shared_deps: u16,
+    pub metrics: NodeMetrics,
 }
 
 fn serialize_name_uses<S>(v: &Option<Arc<AtomicU16>>, s: S) -> Result<S::Ok, S::Error>
@@ -50,9 +85,37 @@ impl Package {
             dep_info,
             is_ws_member,
             is_proc_macro,
+            feature: None,
+            features: Vec::new(),
+            platforms: Vec::new(),
+            diff: None,
+            previous_version: None,
+            license_verdict: None,
+            advisories: Vec::new(),
+            registry: None,
+            footprint: None,
+            build_time: None,
+            unsafe_code: None,
+            on_critical_path: false,
             name_uses: None,
+            exclusive_deps: 0,
+            shared_deps: 0,
+            metrics: NodeMetrics::default(),
+        }
+    }
+
+    /// Id of the package this node belongs to, which for feature nodes is their package's id
+    pub fn package_id(&self) -> &str {
+        match &self.feature {
+            Some(_) => self.id.split_once(" +").map_or(&self.id, |(id, _)| id),
+            None => &self.id,
         }
     }
+
+    /// Number of packages in the graph with the same name, 0 if not counted yet
+    pub fn name_use_count(&self) -> u16 {
+        self.name_uses.as_ref().map(|v| v.load(Ordering::SeqCst)).unwrap_or(0)
+    }
 }
 
 impl Debug for Package {
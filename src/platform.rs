@@ -0,0 +1,194 @@
+use std::{env, process::Command, str::FromStr};
+
+use anyhow::{bail, Context};
+use cargo_platform::{Cfg, Platform};
+
+/// The cfg values that apply to a target triple
+#[derive(Clone, Debug)]
+pub(crate) struct TargetCfg {
+    pub triple: String,
+    cfg: Vec<Cfg>,
+}
+
+impl TargetCfg {
+    /// Asks rustc for the cfg values of `triple`, falling back to a built-in guess based on the
+    /// components of the triple if rustc doesn't know it.
+    pub fn new(triple: &str) -> anyhow::Result<Self> {
+        let cfg = match rustc_cfg(triple) {
+            Ok(cfg) => cfg,
+            Err(e) => {
+                let Some(cfg) = guess_cfg(triple) else {
+                    return Err(e.context(format!("Unknown target {triple}")));
+                };
+                eprintln!("Failed to query rustc for the cfg of {triple}, guessing instead: {e:#}");
+                cfg
+            }
+        };
+        Ok(Self { triple: triple.to_owned(), cfg })
+    }
+
+    /// The platform that rustc compiles for by default, which build scripts, build-dependencies and
+    /// proc-macros are built for
+    pub fn host() -> anyhow::Result<Self> {
+        let version = rustc(&["-vV"])?;
+        let Some(triple) = version.lines().find_map(|line| line.strip_prefix("host: ")) else {
+            bail!("Failed to find the host target in the output of `rustc -vV`");
+        };
+        Self::new(triple)
+    }
+
+    pub fn matches(&self, platform: &Platform) -> bool {
+        platform.matches(&self.triple, &self.cfg)
+    }
+}
+
+fn rustc(args: &[&str]) -> anyhow::Result<String> {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned());
+    let output = Command::new(&rustc)
+        .args(args)
+        .output()
+        .with_context(|| format!("Failed to run {rustc}"))?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn rustc_cfg(triple: &str) -> anyhow::Result<Vec<Cfg>> {
+    rustc(&["--print", "cfg", "--target", triple])?
+        .lines()
+        .map(|line| Cfg::from_str(line).with_context(|| format!("Invalid cfg `{line}`")))
+        .collect()
+}
+
+/// Derives the most commonly used cfg values from a triple like `x86_64-unknown-linux-gnu`
+fn guess_cfg(triple: &str) -> Option<Vec<Cfg>> {
+    let parts: Vec<_> = triple.split('-').collect();
+    // Some triples leave out the vendor, e.g. `aarch64-linux-android`, `thumbv7em-none-eabihf` or
+    // `wasm32-wasip1`
+    let (arch, vendor, rest) = match parts[..] {
+        [arch, "linux" | "none", ..] | [arch, _] => (arch, "unknown", &parts[1..]),
+        [arch, vendor, ..] => (arch, vendor, &parts[2..]),
+        _ => return None,
+    };
+    let (arch, pointer_width) = match arch {
+        "x86_64" => ("x86_64", "64"),
+        "i386" | "i586" | "i686" => ("x86", "32"),
+        "aarch64" | "arm64" => ("aarch64", "64"),
+        "loongarch64" => ("loongarch64", "64"),
+        "powerpc64" | "powerpc64le" => ("powerpc64", "64"),
+        "riscv64gc" | "riscv64" => ("riscv64", "64"),
+        "s390x" => ("s390x", "64"),
+        "wasm32" => ("wasm32", "32"),
+        arch if arch.starts_with("arm") || arch.starts_with("thumb") => ("arm", "32"),
+        _ => return None,
+    };
+    let has = |name: &str| rest.contains(&name);
+    let (os, family) = if has("linux") {
+        (if has("android") || has("androideabi") { "android" } else { "linux" }, Some("unix"))
+    } else if has("darwin") {
+        ("macos", Some("unix"))
+    } else if has("ios") {
+        ("ios", Some("unix"))
+    } else if has("windows") {
+        ("windows", Some("windows"))
+    } else if has("freebsd") {
+        ("freebsd", Some("unix"))
+    } else if rest.iter().any(|part| part.starts_with("wasi")) {
+        ("wasi", Some("wasm"))
+    } else {
+        ("none", None)
+    };
+    let env = ["gnu", "musl", "msvc", "sgx"]
+        .into_iter()
+        .find(|env| rest.last().is_some_and(|last| last.starts_with(env)))
+        .unwrap_or("");
+
+    let mut cfg = vec![
+        Cfg::KeyPair("target_arch".into(), arch.into()),
+        Cfg::KeyPair("target_vendor".into(), vendor.into()),
+        Cfg::KeyPair("target_os".into(), os.into()),
+        Cfg::KeyPair("target_env".into(), env.into()),
+        Cfg::KeyPair("target_pointer_width".into(), pointer_width.into()),
+    ];
+    if let Some(family) = family {
+        cfg.push(Cfg::KeyPair("target_family".into(), family.into()));
+        // `unix` and `windows` are also set on their own
+        if family != "wasm" {
+            cfg.push(Cfg::Name(family.into()));
+        }
+    }
+    Some(cfg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value<'a>(cfg: &'a [Cfg], key: &str) -> Option<&'a str> {
+        cfg.iter().find_map(|cfg| match cfg {
+            Cfg::KeyPair(k, v) if k == key => Some(v.as_str()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn guess_four_part_triple() {
+        let cfg = guess_cfg("x86_64-unknown-linux-gnu").unwrap();
+        assert_eq!(value(&cfg, "target_arch"), Some("x86_64"));
+        assert_eq!(value(&cfg, "target_vendor"), Some("unknown"));
+        assert_eq!(value(&cfg, "target_os"), Some("linux"));
+        assert_eq!(value(&cfg, "target_env"), Some("gnu"));
+        assert_eq!(value(&cfg, "target_pointer_width"), Some("64"));
+        assert!(cfg.contains(&Cfg::Name("unix".into())));
+
+        let cfg = guess_cfg("x86_64-pc-windows-msvc").unwrap();
+        assert_eq!(value(&cfg, "target_vendor"), Some("pc"));
+        assert_eq!(value(&cfg, "target_os"), Some("windows"));
+        assert_eq!(value(&cfg, "target_env"), Some("msvc"));
+        assert!(cfg.contains(&Cfg::Name("windows".into())));
+    }
+
+    #[test]
+    fn guess_three_part_triple_with_vendor() {
+        let cfg = guess_cfg("aarch64-apple-darwin").unwrap();
+        assert_eq!(value(&cfg, "target_arch"), Some("aarch64"));
+        assert_eq!(value(&cfg, "target_vendor"), Some("apple"));
+        assert_eq!(value(&cfg, "target_os"), Some("macos"));
+        assert_eq!(value(&cfg, "target_env"), Some(""));
+
+        let cfg = guess_cfg("x86_64-unknown-freebsd").unwrap();
+        assert_eq!(value(&cfg, "target_vendor"), Some("unknown"));
+        assert_eq!(value(&cfg, "target_os"), Some("freebsd"));
+    }
+
+    #[test]
+    fn guess_triple_without_vendor() {
+        let cfg = guess_cfg("aarch64-linux-android").unwrap();
+        assert_eq!(value(&cfg, "target_vendor"), Some("unknown"));
+        assert_eq!(value(&cfg, "target_os"), Some("android"));
+        assert_eq!(value(&cfg, "target_env"), Some(""));
+        assert!(cfg.contains(&Cfg::Name("unix".into())));
+
+        let cfg = guess_cfg("armv7-linux-androideabi").unwrap();
+        assert_eq!(value(&cfg, "target_arch"), Some("arm"));
+        assert_eq!(value(&cfg, "target_os"), Some("android"));
+
+        let cfg = guess_cfg("thumbv7em-none-eabihf").unwrap();
+        assert_eq!(value(&cfg, "target_vendor"), Some("unknown"));
+        assert_eq!(value(&cfg, "target_os"), Some("none"));
+        assert_eq!(value(&cfg, "target_family"), None);
+
+        let cfg = guess_cfg("wasm32-wasip1").unwrap();
+        assert_eq!(value(&cfg, "target_vendor"), Some("unknown"));
+        assert_eq!(value(&cfg, "target_os"), Some("wasi"));
+        assert_eq!(value(&cfg, "target_family"), Some("wasm"));
+        assert_eq!(value(&cfg, "target_pointer_width"), Some("32"));
+    }
+
+    #[test]
+    fn guess_unknown_triples() {
+        assert!(guess_cfg("x86_64").is_none());
+        assert!(guess_cfg("mips-unknown-linux-gnu").is_none());
+    }
+}
@@ -0,0 +1,159 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use cargo_metadata::{semver::Version, Source};
+use serde::Deserialize;
+
+/// A release of a crate, as listed in the registry index
+#[derive(Clone, Debug)]
+pub(crate) struct Release {
+    pub version: Version,
+    pub yanked: bool,
+}
+
+/// Line of an index file, see <https://doc.rust-lang.org/cargo/reference/registry-index.html>
+#[derive(Deserialize)]
+struct IndexEntry {
+    vers: String,
+    #[serde(default)]
+    yanked: bool,
+}
+
+/// Read access to the registry indices that cargo already downloaded to
+/// `$CARGO_HOME/registry/index`, without any network access
+pub(crate) struct RegistryCache {
+    /// One directory per registry, named after its host and a hash of its url
+    dirs: Vec<PathBuf>,
+}
+
+impl RegistryCache {
+    pub fn new() -> Self {
+        let cargo_home = env::var_os("CARGO_HOME").map(PathBuf::from).or_else(|| {
+            env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")).map(|home| {
+                let mut home = PathBuf::from(home);
+                home.push(".cargo");
+                home
+            })
+        });
+        let dirs = cargo_home
+            .and_then(|home| fs::read_dir(home.join("registry").join("index")).ok())
+            .map(|dirs| dirs.filter_map(|dir| Some(dir.ok()?.path())).collect())
+            .unwrap_or_default();
+        Self { dirs }
+    }
+
+    /// All releases of the crate `name` from the registry `source`, or `None` if cargo has not
+    /// cached its index entry
+    pub fn releases(&self, source: &Source, name: &str) -> Option<Vec<Release>> {
+        let hosts = registry_hosts(source)?;
+        let file = self
+            .dirs
+            .iter()
+            .filter(|dir| {
+                let dir_name = dir.file_name().unwrap_or_default().to_string_lossy();
+                hosts.iter().any(|host| {
+                    dir_name.strip_prefix(host.as_str()).is_some_and(|rest| rest.starts_with('-'))
+                })
+            })
+            .map(|dir| dir.join(".cache").join(index_path(&name.to_lowercase())))
+            .filter_map(|file| Some((modified(&file)?, file)))
+            .max()?
+            .1;
+        let data = fs::read(file).ok()?;
+        Some(parse_cache(&data))
+    }
+}
+
+/// Host names that the index directory of `source` may start with
+fn registry_hosts(source: &Source) -> Option<Vec<String>> {
+    if source.is_crates_io() {
+        // Used by the sparse and the git protocol respectively
+        return Some(vec!["index.crates.io".to_owned(), "github.com".to_owned()]);
+    }
+    let url = source.repr.strip_prefix("registry+").or(source.repr.strip_prefix("sparse+"))?;
+    let host = url.split_once("://")?.1.split(['/', ':']).next()?;
+    Some(vec![host.to_owned()])
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).ok()?.modified().ok()
+}
+
+/// Location of the index file of a crate, relative to the root of the index
+fn index_path(name: &str) -> PathBuf {
+    match name.len() {
+        1 => ["1", name].iter().collect(),
+        2 => ["2", name].iter().collect(),
+        3 => ["3", &name[..1], name].iter().collect(),
+        _ => [&name[..2], &name[2..4], name].iter().collect(),
+    }
+}
+
+/// Parses a file of cargo's index cache, which is a small binary header followed by
+/// NUL-terminated pairs of a version and the JSON index entry of that version
+fn parse_cache(data: &[u8]) -> Vec<Release> {
+    data.split(|&b| b == 0)
+        .filter(|part| part.first() == Some(&b'{'))
+        .filter_map(|part| serde_json::from_slice::<IndexEntry>(part).ok())
+        .filter_map(|entry| {
+            Some(Release { version: Version::parse(&entry.vers).ok()?, yanked: entry.yanked })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_path_of_short_names() {
+        assert_eq!(index_path("a"), Path::new("1/a"));
+        assert_eq!(index_path("cc"), Path::new("2/cc"));
+        assert_eq!(index_path("syn"), Path::new("3/s/syn"));
+    }
+
+    #[test]
+    fn index_path_of_long_names() {
+        assert_eq!(index_path("toml"), Path::new("to/ml/toml"));
+        assert_eq!(index_path("serde_json"), Path::new("se/rd/serde_json"));
+    }
+
+    #[test]
+    fn parse_cache_entries() {
+        // Cache version, index format version and the etag of the index file, followed by the
+        // version and index entry of every release
+        let mut data = vec![3, 2, 0, 0, 0];
+        data.extend_from_slice(b"etag: \"abc\"\0");
+        for (version, entry) in [
+            ("1.0.0", r#"{"name":"foo","vers":"1.0.0","deps":[],"cksum":"00"}"#),
+            ("1.0.1", r#"{"name":"foo","vers":"1.0.1","deps":[],"cksum":"01","yanked":true}"#),
+            ("1.1.0-rc.1", r#"{"name":"foo","vers":"1.1.0-rc.1","yanked":false}"#),
+            ("2.0.0", r#"{"name":"foo","vers":"not a version"}"#),
+        ] {
+            data.extend_from_slice(version.as_bytes());
+            data.push(0);
+            data.extend_from_slice(entry.as_bytes());
+            data.push(0);
+        }
+
+        let releases: Vec<_> =
+            parse_cache(&data).into_iter().map(|r| (r.version.to_string(), r.yanked)).collect();
+        assert_eq!(
+            releases,
+            [
+                ("1.0.0".to_owned(), false),
+                ("1.0.1".to_owned(), true),
+                ("1.1.0-rc.1".to_owned(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_empty_cache() {
+        assert!(parse_cache(&[]).is_empty());
+        assert!(parse_cache(&[3, 2, 0, 0, 0]).is_empty());
+    }
+}
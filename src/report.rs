@@ -0,0 +1,8 @@
+pub(crate) mod advisories;
+pub(crate) mod cycles;
+pub(crate) mod duplicates;
+pub(crate) mod licenses;
+pub(crate) mod metrics;
+pub(crate) mod outdated;
+pub(crate) mod unsafe_code;
+pub(crate) mod why;
@@ -0,0 +1,263 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context};
+use cargo_metadata::semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
+use super::why::paths;
+use crate::graph::{DepGraph, DepMap};
+
+/// Maximum number of dependency paths printed per package
+const MAX_PRINTED_PATHS: usize = 5;
+
+/// What an advisory is about, least severe first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum AdvisoryKind {
+    Notice,
+    Unmaintained,
+    Unsound,
+    Vulnerability,
+}
+
+impl AdvisoryKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Notice => "notice",
+            Self::Unmaintained => "unmaintained",
+            Self::Unsound => "unsound",
+            Self::Vulnerability => "vulnerability",
+        }
+    }
+}
+
+/// An advisory of the RustSec database that applies to a package
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct Advisory {
+    pub id: String,
+    pub kind: AdvisoryKind,
+    pub title: String,
+    pub date: String,
+    pub url: Option<String>,
+    pub aliases: Vec<String>,
+    /// Version requirements of the releases that fix the issue
+    pub patched: Vec<String>,
+}
+
+/// The front matter of an advisory file, in the format of
+/// <https://github.com/rustsec/advisory-db>
+#[derive(Deserialize)]
+struct AdvisoryFile {
+    advisory: AdvisoryMeta,
+    #[serde(default)]
+    versions: AdvisoryVersions,
+}
+
+#[derive(Deserialize)]
+struct AdvisoryMeta {
+    id: String,
+    package: String,
+    date: String,
+    url: Option<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    informational: Option<String>,
+    withdrawn: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+struct AdvisoryVersions {
+    #[serde(default)]
+    patched: Vec<String>,
+    #[serde(default)]
+    unaffected: Vec<String>,
+}
+
+/// Advisory that is parsed and ready for matching against versions
+struct Entry {
+    advisory: Advisory,
+    /// Both patched and unaffected versions
+    safe: Vec<VersionReq>,
+}
+
+/// Advisories of a local checkout of the RustSec advisory database, by crate name
+pub(crate) struct AdvisoryDb {
+    advisories: HashMap<String, Vec<Entry>>,
+}
+
+impl AdvisoryDb {
+    /// Reads all advisories for crates, skipping withdrawn ones and files that can't be parsed
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let crates = path.join("crates");
+        if !crates.is_dir() {
+            bail!("{} is not a RustSec advisory database", path.display());
+        }
+
+        let mut advisories = HashMap::<String, Vec<Entry>>::new();
+        for file in advisory_files(&crates)? {
+            match parse_advisory(&file) {
+                Ok(Some((package, entry))) => advisories.entry(package).or_default().push(entry),
+                Ok(None) => {}
+                Err(e) => eprintln!("Skipping advisory {}: {e:#}", file.display()),
+            }
+        }
+        Ok(Self { advisories })
+    }
+
+    /// Advisories that apply to a crates.io package, most severe first
+    pub fn matches(&self, name: &str, version: &Version) -> Vec<Advisory> {
+        let mut res: Vec<_> = self
+            .advisories
+            .get(name)
+            .into_iter()
+            .flatten()
+            .filter(|entry| !entry.safe.iter().any(|req| req.matches(version)))
+            .map(|entry| entry.advisory.clone())
+            .collect();
+        res.sort_by(|a, b| b.kind.cmp(&a.kind).then(a.id.cmp(&b.id)));
+        res
+    }
+}
+
+fn advisory_files(crates: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for dir in fs::read_dir(crates)? {
+        let dir = dir?.path();
+        if !dir.is_dir() {
+            continue;
+        }
+        for file in fs::read_dir(&dir)? {
+            let file = file?.path();
+            if file.extension().is_some_and(|ext| ext == "md") {
+                files.push(file);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Parses an advisory, which is Markdown with TOML front matter in a fenced code block followed
+/// by the title as a heading
+fn parse_advisory(path: &Path) -> anyhow::Result<Option<(String, Entry)>> {
+    let text = fs::read_to_string(path)?;
+    let (front_matter, body) = text
+        .trim_start()
+        .strip_prefix("```toml")
+        .and_then(|rest| rest.split_once("\n```"))
+        .context("Missing TOML front matter")?;
+    let file: AdvisoryFile = toml::from_str(front_matter)?;
+    let meta = file.advisory;
+    if meta.withdrawn.is_some() {
+        return Ok(None);
+    }
+
+    let kind = match meta.informational.as_deref() {
+        None => AdvisoryKind::Vulnerability,
+        Some("unmaintained") => AdvisoryKind::Unmaintained,
+        Some("unsound") => AdvisoryKind::Unsound,
+        Some(_) => AdvisoryKind::Notice,
+    };
+    let title =
+        body.lines().find_map(|line| line.strip_prefix("# ")).unwrap_or_default().trim().to_owned();
+    let safe = file
+        .versions
+        .patched
+        .iter()
+        .chain(&file.versions.unaffected)
+        .map(|req| VersionReq::parse(req).with_context(|| format!("Invalid version `{req}`")))
+        .collect::<anyhow::Result<_>>()?;
+
+    let advisory = Advisory {
+        id: meta.id,
+        kind,
+        title,
+        date: meta.date,
+        url: meta.url,
+        aliases: meta.aliases,
+        patched: file.versions.patched,
+    };
+    Ok(Some((meta.package, Entry { advisory, safe })))
+}
+
+/// Attaches the advisories that apply to each package from crates.io
+pub(crate) fn set_advisories(graph: &mut DepGraph, depmap: &DepMap, db: &AdvisoryDb) {
+    for pkg in graph.node_weights_mut() {
+        // Feature nodes would only repeat their package
+        if pkg.feature.is_some() {
+            continue;
+        }
+        let Some(meta) = depmap.get(&pkg.id) else {
+            continue;
+        };
+        if meta.source.as_ref().is_some_and(|source| source.is_crates_io()) {
+            pkg.advisories = db.matches(&pkg.name, &pkg.version);
+        }
+    }
+}
+
+/// A package that advisories apply to
+#[derive(Debug, Clone, Serialize)]
+pub struct AffectedPackage {
+    pub id: String,
+    pub advisories: Vec<Advisory>,
+    /// Chains of package ids from the workspace members to the package, shortest first
+    pub paths: Vec<Vec<String>>,
+    /// Whether there are more chains than were collected
+    pub truncated: bool,
+}
+
+/// Lists the packages with advisories, most severe first
+pub(crate) fn advisories(graph: &DepGraph) -> Vec<AffectedPackage> {
+    let mut res: Vec<_> = graph
+        .node_indices()
+        .filter(|&idx| !graph[idx].advisories.is_empty())
+        .map(|idx| {
+            let (paths, truncated) = paths(graph, idx);
+            AffectedPackage {
+                id: graph[idx].id.clone(),
+                advisories: graph[idx].advisories.clone(),
+                paths,
+                truncated,
+            }
+        })
+        .collect();
+    res.sort_by(|a, b| b.advisories[0].kind.cmp(&a.advisories[0].kind).then(a.id.cmp(&b.id)));
+    res
+}
+
+pub(crate) fn print(out: &mut dyn Write, graph: &DepGraph) -> anyhow::Result<()> {
+    let affected = advisories(graph);
+    if affected.is_empty() {
+        writeln!(out, "No advisories apply to the packages in the graph")?;
+        return Ok(());
+    }
+
+    for (i, pkg) in affected.iter().enumerate() {
+        if i > 0 {
+            writeln!(out)?;
+        }
+        writeln!(out, "{}", pkg.id)?;
+        for advisory in &pkg.advisories {
+            let kind = advisory.kind.as_str();
+            writeln!(out, "  {} ({kind}): {}", advisory.id, advisory.title)?;
+            if !advisory.patched.is_empty() {
+                writeln!(out, "    patched: {}", advisory.patched.join(", "))?;
+            }
+        }
+        for path in pkg.paths.iter().take(MAX_PRINTED_PATHS) {
+            writeln!(out, "  via {}", path.join(" -> "))?;
+        }
+        let more = pkg.paths.len().saturating_sub(MAX_PRINTED_PATHS);
+        if more > 0 || pkg.truncated {
+            let plus = if pkg.truncated { "+" } else { "" };
+            writeln!(out, "  and {more}{plus} more paths")?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,196 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::{self, Write},
+};
+
+use petgraph::{algo::tarjan_scc, stable_graph::NodeIndex, visit::EdgeRef};
+use serde::Serialize;
+
+use crate::{dep_info::DepInfo, graph::DepGraph};
+
+/// Packages that depend on each other in a loop
+#[derive(Debug, Clone, Serialize)]
+pub struct Cycle {
+    pub packages: Vec<String>,
+    /// Edges between the packages of the cycle
+    pub edges: Vec<CycleEdge>,
+    /// Whether the loop is only closed by dev-dependencies, so it doesn't prevent building the
+    /// packages but makes them build twice when testing
+    pub dev_only: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CycleEdge {
+    pub source: String,
+    pub target: String,
+    /// `normal`, `dev` or `build`
+    pub kind: String,
+}
+
+/// The graph with every cycle collapsed into a single node, which makes it acyclic
+#[derive(Debug, Clone, Serialize)]
+pub struct Condensation {
+    pub nodes: Vec<CondensedNode>,
+    pub edges: Vec<CondensedEdge>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CondensedNode {
+    /// Id of the package, or the first id of the packages in a cycle
+    pub id: String,
+    pub packages: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CondensedEdge {
+    pub source: String,
+    pub target: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CyclesInfo {
+    pub cycles: Vec<Cycle>,
+    pub condensation: Condensation,
+}
+
+/// Groups of packages that can reach each other, as found by Tarjan's algorithm. Packages that
+/// are not part of any cycle form groups of their own.
+fn components(graph: &DepGraph) -> Vec<Vec<NodeIndex<u16>>> {
+    let mut components = tarjan_scc(graph);
+    for component in &mut components {
+        component.sort_by(|&a, &b| graph[a].id.cmp(&graph[b].id));
+    }
+    components.sort_by(|a, b| graph[a[0]].id.cmp(&graph[b[0]].id));
+    components
+}
+
+fn is_cycle(graph: &DepGraph, component: &[NodeIndex<u16>]) -> bool {
+    match component {
+        [idx] => graph.contains_edge(*idx, *idx),
+        _ => true,
+    }
+}
+
+/// Marks the edges that are part of a cycle
+pub(crate) fn set_cycle_edges(graph: &mut DepGraph) {
+    let mut component_of = HashMap::new();
+    for (i, component) in components(graph).into_iter().enumerate() {
+        if is_cycle(graph, &component) {
+            component_of.extend(component.into_iter().map(|idx| (idx, i)));
+        }
+    }
+    for edge in graph.edge_indices().collect::<Vec<_>>() {
+        let (source, target) = graph.edge_endpoints(edge).unwrap();
+        graph[edge].in_cycle = component_of
+            .get(&source)
+            .is_some_and(|component| component_of.get(&target) == Some(component));
+    }
+}
+
+pub(crate) fn cycles(graph: &DepGraph) -> CyclesInfo {
+    let components = components(graph);
+    let mut component_of = HashMap::new();
+    for (i, component) in components.iter().enumerate() {
+        component_of.extend(component.iter().map(|&idx| (idx, i)));
+    }
+
+    let mut cycles = Vec::new();
+    for component in components.iter().filter(|c| is_cycle(graph, c)) {
+        let edges: Vec<_> = component
+            .iter()
+            .flat_map(|&idx| graph.edges(idx))
+            .filter(|edge| component_of[&edge.target()] == component_of[&edge.source()])
+            .map(|edge| edge.weight())
+            .collect();
+        cycles.push(Cycle {
+            packages: component.iter().map(|&idx| graph[idx].id.clone()).collect(),
+            dev_only: !has_cycle(edges.iter().copied().filter(|dep| !is_dev_only(dep))),
+            edges: edges
+                .into_iter()
+                .map(|dep| CycleEdge {
+                    source: dep.source.clone(),
+                    target: dep.target.clone(),
+                    kind: kind_name(dep).to_owned(),
+                })
+                .collect(),
+        });
+    }
+
+    let mut condensed_edges = BTreeSet::new();
+    for edge in graph.edge_indices() {
+        let (source, target) = graph.edge_endpoints(edge).unwrap();
+        let (source, target) = (component_of[&source], component_of[&target]);
+        if source != target {
+            condensed_edges.insert((source, target));
+        }
+    }
+    let id = |i: usize| graph[components[i][0]].id.clone();
+    let condensation = Condensation {
+        nodes: (0..components.len())
+            .map(|i| CondensedNode {
+                id: id(i),
+                packages: components[i].iter().map(|&idx| graph[idx].id.clone()).collect(),
+            })
+            .collect(),
+        edges: condensed_edges
+            .into_iter()
+            .map(|(source, target)| CondensedEdge { source: id(source), target: id(target) })
+            .collect(),
+    };
+
+    CyclesInfo { cycles, condensation }
+}
+
+/// Whether `edges` still form a cycle, by repeatedly removing the edges of packages without
+/// dependents
+fn has_cycle<'a>(edges: impl Iterator<Item = &'a DepInfo>) -> bool {
+    let mut edges: Vec<_> = edges.map(|dep| (dep.source.as_str(), dep.target.as_str())).collect();
+    loop {
+        let roots: Vec<_> = edges
+            .iter()
+            .map(|&(source, _)| source)
+            .filter(|&source| !edges.iter().any(|&(_, target)| target == source))
+            .collect();
+        if roots.is_empty() {
+            return !edges.is_empty();
+        }
+        edges.retain(|(source, _)| !roots.contains(source));
+    }
+}
+
+fn is_dev_only(dep: &DepInfo) -> bool {
+    dep.inner.is_dev && !dep.inner.is_normal && !dep.inner.is_build
+}
+
+fn kind_name(dep: &DepInfo) -> &'static str {
+    if is_dev_only(dep) {
+        "dev"
+    } else if dep.inner.is_build && !dep.inner.is_normal {
+        "build"
+    } else {
+        "normal"
+    }
+}
+
+pub(crate) fn print(out: &mut dyn Write, graph: &DepGraph) -> io::Result<()> {
+    let cycles = cycles(graph).cycles;
+    if cycles.is_empty() {
+        return writeln!(out, "The dependency graph contains no cycles");
+    }
+
+    for (i, cycle) in cycles.iter().enumerate() {
+        if i > 0 {
+            writeln!(out)?;
+        }
+        let closed_by = if cycle.dev_only { ", closed by dev-dependencies" } else { "" };
+        writeln!(out, "Cycle of {} packages{closed_by}", cycle.packages.len())?;
+        for edge in &cycle.edges {
+            let kind = match edge.kind.as_str() {
+                "normal" => String::new(),
+                kind => format!(" ({kind})"),
+            };
+            writeln!(out, "  {} -> {}{kind}", edge.source, edge.target)?;
+        }
+    }
+    Ok(())
+}
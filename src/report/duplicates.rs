@@ -0,0 +1,133 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+use cargo_metadata::DependencyKind;
+use petgraph::{stable_graph::NodeIndex, Direction};
+use serde::Serialize;
+
+use crate::graph::{shortest_chain, DepGraph, DepMap};
+
+/// A package name that occurs with more than one version in the graph
+#[derive(Debug, Clone, Serialize)]
+pub struct Duplicate {
+    pub name: String,
+    /// All versions of the package, oldest first
+    pub versions: Vec<DuplicateVersion>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateVersion {
+    pub id: String,
+    pub version: String,
+    pub dependents: Vec<Requirement>,
+    /// Ids of the packages on the shortest chain from a workspace member to this version
+    pub shortest_chain: Vec<String>,
+}
+
+/// A version requirement one package has on another
+#[derive(Debug, Clone, Serialize)]
+pub struct Requirement {
+    pub id: String,
+    pub req: String,
+    /// `normal`, `dev` or `build`
+    pub kind: String,
+}
+
+/// Lists every package name that occurs with multiple versions, along with what pulls them in
+pub(crate) fn duplicates(graph: &DepGraph, depmap: &DepMap) -> Vec<Duplicate> {
+    let mut by_name = BTreeMap::<&str, Vec<NodeIndex<u16>>>::new();
+    for idx in graph.node_indices() {
+        by_name.entry(&graph[idx].name).or_default().push(idx);
+    }
+
+    by_name
+        .into_iter()
+        .filter(|(_, packages)| packages.len() > 1)
+        .map(|(name, mut packages)| {
+            packages.sort_by(|&a, &b| graph[a].version.cmp(&graph[b].version));
+            let versions = packages
+                .into_iter()
+                .map(|idx| DuplicateVersion {
+                    id: graph[idx].id.clone(),
+                    version: graph[idx].version.to_string(),
+                    dependents: requirements(graph, depmap, idx),
+                    shortest_chain: shortest_chain(graph, idx)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|idx| graph[idx].id.clone())
+                        .collect(),
+                })
+                .collect();
+            Duplicate { name: name.to_owned(), versions }
+        })
+        .collect()
+}
+
+fn requirements(graph: &DepGraph, depmap: &DepMap, idx: NodeIndex<u16>) -> Vec<Requirement> {
+    let pkg = &graph[idx];
+    let mut dependents: Vec<_> = graph.neighbors_directed(idx, Direction::Incoming).collect();
+    dependents.sort_by(|&a, &b| graph[a].id.cmp(&graph[b].id));
+    dependents.dedup();
+
+    let mut res = Vec::new();
+    for dependent in dependents {
+        let id = &graph[dependent].id;
+        let Some(meta) = depmap.get(id) else {
+            continue;
+        };
+        // A package can depend on the same crate more than once, e.g. with different
+        // requirements for different targets or dependency kinds
+        let mut reqs: Vec<_> = meta
+            .dependencies
+            .iter()
+            .filter(|dep| dep.name == pkg.name && dep.req.matches(&pkg.version))
+            .map(|dep| (dep.req.to_string(), kind_name(dep.kind)))
+            .collect();
+        reqs.sort_unstable();
+        reqs.dedup();
+        res.extend(reqs.into_iter().map(|(req, kind)| Requirement {
+            id: id.clone(),
+            req,
+            kind: kind.to_owned(),
+        }));
+    }
+    res
+}
+
+fn kind_name(kind: DependencyKind) -> &'static str {
+    match kind {
+        DependencyKind::Development => "dev",
+        DependencyKind::Build => "build",
+        _ => "normal",
+    }
+}
+
+pub(crate) fn print(out: &mut dyn Write, graph: &DepGraph, depmap: &DepMap) -> io::Result<()> {
+    let duplicates = duplicates(graph, depmap);
+    if duplicates.is_empty() {
+        return writeln!(out, "No package occurs with more than one version");
+    }
+
+    for (i, dup) in duplicates.iter().enumerate() {
+        if i > 0 {
+            writeln!(out)?;
+        }
+        writeln!(out, "{} ({} versions)", dup.name, dup.versions.len())?;
+        for version in &dup.versions {
+            writeln!(out, "  {}", version.version)?;
+            for dependent in &version.dependents {
+                let kind = match dependent.kind.as_str() {
+                    "normal" => String::new(),
+                    kind => format!(" ({kind})"),
+                };
+                writeln!(out, "    required as {} by {}{kind}", dependent.req, dependent.id)?;
+            }
+            if !version.shortest_chain.is_empty() {
+                writeln!(out, "    via {}", version.shortest_chain.join(" -> "))?;
+            }
+        }
+    }
+    Ok(())
+}
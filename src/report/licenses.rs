@@ -0,0 +1,281 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::Write,
+    path::Path,
+};
+
+use anyhow::Context;
+use cargo_metadata::Package as MetaPackage;
+use serde::{Deserialize, Serialize};
+use spdx::{Expression, LicenseItem, LicenseReq, ParseMode};
+
+use crate::{
+    graph::{DepGraph, DepMap},
+    package::Package,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Verdict {
+    Allow,
+    Review,
+    Deny,
+}
+
+/// Which licenses are acceptable, read from a TOML file.
+///
+/// Entries are SPDX license ids like `MIT`, or full requirements like
+/// `Apache-2.0 WITH LLVM-exception`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct LicensePolicy {
+    allow: Vec<String>,
+    review: Vec<String>,
+    deny: Vec<String>,
+    /// Verdict for licenses that are in none of the lists, `review` by default
+    unlisted: Option<Verdict>,
+    /// Strictest verdict for packages that are only pulled in by dev-dependencies
+    dev_only: Option<Verdict>,
+    /// Additionally allowed licenses per crate name
+    exceptions: HashMap<String, Vec<String>>,
+}
+
+impl LicensePolicy {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read license policy {}", path.display()))?;
+        toml::from_str(&text)
+            .with_context(|| format!("Failed to parse license policy {}", path.display()))
+    }
+
+    pub fn evaluate(&self, pkg: &Package, meta: &MetaPackage) -> Verdict {
+        let info = &pkg.dep_info;
+        let dev_only = info.is_dev && !info.is_normal && !info.is_build;
+        self.verdict(&pkg.name, meta.license.as_deref(), dev_only)
+    }
+
+    /// Verdict on the `license` expression of a crate
+    fn verdict(&self, crate_name: &str, license: Option<&str>, dev_only: bool) -> Verdict {
+        let unlisted = self.unlisted.unwrap_or(Verdict::Review);
+        let verdict = match license.map(|l| Expression::parse_mode(l, ParseMode::LAX)) {
+            Some(Ok(expr)) => [Verdict::Allow, Verdict::Review]
+                .into_iter()
+                .find(|&max| expr.evaluate(|req| self.license_verdict(crate_name, req) <= max))
+                .unwrap_or(Verdict::Deny),
+            // No license, a license file or something that is not an SPDX expression, so there
+            // is nothing to match against
+            Some(Err(_)) | None => unlisted.max(Verdict::Review),
+        };
+
+        if dev_only {
+            verdict.min(self.dev_only.unwrap_or(Verdict::Deny))
+        } else {
+            verdict
+        }
+    }
+
+    fn license_verdict(&self, crate_name: &str, req: &LicenseReq) -> Verdict {
+        let full = req.to_string();
+        let license = match &req.license {
+            LicenseItem::Spdx { id, .. } => id.name.to_owned(),
+            LicenseItem::Other { lic_ref, .. } => format!("LicenseRef-{lic_ref}"),
+        };
+        let listed = |list: &[String]| list.iter().any(|l| *l == full || *l == license);
+
+        if self.exceptions.get(crate_name).is_some_and(|l| listed(l)) {
+            Verdict::Allow
+        } else if listed(&self.deny) {
+            Verdict::Deny
+        } else if listed(&self.allow) {
+            Verdict::Allow
+        } else if listed(&self.review) {
+            Verdict::Review
+        } else {
+            self.unlisted.unwrap_or(Verdict::Review)
+        }
+    }
+}
+
+/// Sets the verdict of `policy` on all packages, except for workspace members
+///
+/// Feature nodes get the verdict of their package.
+pub(crate) fn set_license_verdicts(graph: &mut DepGraph, depmap: &DepMap, policy: &LicensePolicy) {
+    let mut verdicts = HashMap::new();
+    for pkg in graph.node_weights_mut().filter(|pkg| pkg.feature.is_none()) {
+        if pkg.is_ws_member {
+            continue;
+        }
+        if let Some(meta) = depmap.get(&pkg.id) {
+            let verdict = policy.evaluate(pkg, meta);
+            pkg.license_verdict = Some(verdict);
+            verdicts.insert(pkg.id.clone(), verdict);
+        }
+    }
+    for pkg in graph.node_weights_mut().filter(|pkg| pkg.feature.is_some()) {
+        pkg.license_verdict = verdicts.get(pkg.package_id()).copied();
+    }
+}
+
+/// Packages that share the same license expression
+#[derive(Debug, Clone, Serialize)]
+pub struct LicenseGroup {
+    pub license: String,
+    /// Strictest verdict of the packages in this group, if a policy was given
+    pub verdict: Option<Verdict>,
+    pub packages: Vec<String>,
+}
+
+/// Groups the packages in the graph by license, most problematic first
+pub(crate) fn licenses(graph: &DepGraph, depmap: &DepMap) -> Vec<LicenseGroup> {
+    let mut groups = BTreeMap::<String, LicenseGroup>::new();
+    for pkg in graph.node_weights() {
+        // Feature nodes would only repeat their package
+        if pkg.is_ws_member || pkg.feature.is_some() {
+            continue;
+        }
+        let Some(meta) = depmap.get(&pkg.id) else {
+            continue;
+        };
+        let license = match (&meta.license, &meta.license_file) {
+            (Some(license), _) => license.clone(),
+            (None, Some(_)) => "(license file)".to_owned(),
+            (None, None) => "(none)".to_owned(),
+        };
+        let group = groups.entry(license.clone()).or_insert_with(|| LicenseGroup {
+            license,
+            verdict: None,
+            packages: Vec::new(),
+        });
+        group.verdict = group.verdict.max(pkg.license_verdict);
+        group.packages.push(pkg.id.clone());
+    }
+
+    let mut groups: Vec<_> = groups.into_values().collect();
+    for group in &mut groups {
+        group.packages.sort_unstable();
+    }
+    groups.sort_by(|a, b| b.verdict.cmp(&a.verdict).then(b.packages.len().cmp(&a.packages.len())));
+    groups
+}
+
+/// Prints the licenses that need review or are denied, failing if the policy denies any of them
+pub(crate) fn check(out: &mut dyn Write, graph: &DepGraph, depmap: &DepMap) -> anyhow::Result<()> {
+    // Allowed packages are not interesting when checking
+    let groups: Vec<_> = licenses(graph, depmap)
+        .into_iter()
+        .filter(|group| group.verdict > Some(Verdict::Allow))
+        .collect();
+    if groups.is_empty() {
+        writeln!(out, "All licenses are allowed by the policy")?;
+    }
+    for group in &groups {
+        let verdict = match group.verdict {
+            Some(Verdict::Deny) => "deny",
+            _ => "review",
+        };
+        writeln!(out, "{verdict:<7}{} ({})", group.license, group.packages.len())?;
+        for pkg in graph.node_weights().filter(|pkg| group.packages.contains(&pkg.id)) {
+            if pkg.license_verdict > Some(Verdict::Allow) {
+                writeln!(out, "         {}", pkg.id)?;
+            }
+        }
+    }
+
+    let denied = graph.node_weights().filter(|pkg| pkg.license_verdict == Some(Verdict::Deny));
+    let denied = denied.filter(|pkg| pkg.feature.is_none()).count();
+    if denied > 0 {
+        anyhow::bail!("{denied} packages violate the license policy");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(toml: &str) -> LicensePolicy {
+        toml::from_str(toml).unwrap()
+    }
+
+    const POLICY: &str = r#"
+        allow = ["MIT", "Apache-2.0", "Apache-2.0 WITH LLVM-exception"]
+        review = ["MPL-2.0"]
+        deny = ["GPL-3.0"]
+
+        [exceptions]
+        ring = ["ISC"]
+    "#;
+
+    #[test]
+    fn single_licenses() {
+        let policy = parse(POLICY);
+        assert_eq!(policy.verdict("foo", Some("MIT"), false), Verdict::Allow);
+        assert_eq!(policy.verdict("foo", Some("MPL-2.0"), false), Verdict::Review);
+        assert_eq!(policy.verdict("foo", Some("GPL-3.0"), false), Verdict::Deny);
+        // Unlisted licenses need review by default
+        assert_eq!(policy.verdict("foo", Some("ISC"), false), Verdict::Review);
+    }
+
+    #[test]
+    fn or_takes_the_most_permissive_choice() {
+        let policy = parse(POLICY);
+        assert_eq!(policy.verdict("foo", Some("MIT OR GPL-3.0"), false), Verdict::Allow);
+        assert_eq!(policy.verdict("foo", Some("MPL-2.0 OR GPL-3.0"), false), Verdict::Review);
+        // Deprecated syntax of cargo
+        assert_eq!(policy.verdict("foo", Some("MIT/Apache-2.0"), false), Verdict::Allow);
+    }
+
+    #[test]
+    fn and_takes_the_strictest_requirement() {
+        let policy = parse(POLICY);
+        assert_eq!(policy.verdict("foo", Some("MIT AND Apache-2.0"), false), Verdict::Allow);
+        assert_eq!(policy.verdict("foo", Some("MIT AND MPL-2.0"), false), Verdict::Review);
+        assert_eq!(policy.verdict("foo", Some("MIT AND GPL-3.0"), false), Verdict::Deny);
+        assert_eq!(
+            policy.verdict("foo", Some("(MIT OR Apache-2.0) AND GPL-3.0"), false),
+            Verdict::Deny
+        );
+    }
+
+    #[test]
+    fn with_exceptions() {
+        let policy = parse(POLICY);
+        assert_eq!(
+            policy.verdict("foo", Some("Apache-2.0 WITH LLVM-exception"), false),
+            Verdict::Allow
+        );
+        // The license on its own is listed as well
+        assert_eq!(
+            policy.verdict("foo", Some("GPL-3.0 WITH Classpath-exception-2.0"), false),
+            Verdict::Deny
+        );
+    }
+
+    #[test]
+    fn crate_exceptions() {
+        let policy = parse(POLICY);
+        assert_eq!(policy.verdict("ring", Some("MIT AND ISC"), false), Verdict::Allow);
+        assert_eq!(policy.verdict("foo", Some("MIT AND ISC"), false), Verdict::Review);
+    }
+
+    #[test]
+    fn missing_or_invalid_licenses() {
+        let policy = parse(POLICY);
+        assert_eq!(policy.verdict("foo", None, false), Verdict::Review);
+        assert_eq!(policy.verdict("foo", Some("see LICENSE file"), false), Verdict::Review);
+
+        let strict = parse(r#"unlisted = "deny""#);
+        assert_eq!(strict.verdict("foo", None, false), Verdict::Deny);
+    }
+
+    #[test]
+    fn dev_only_packages() {
+        let policy = parse(POLICY);
+        assert_eq!(policy.verdict("foo", Some("GPL-3.0"), true), Verdict::Deny);
+
+        let lenient = parse("deny = [\"GPL-3.0\"]\ndev-only = \"review\"");
+        assert_eq!(lenient.verdict("foo", Some("GPL-3.0"), true), Verdict::Review);
+        assert_eq!(lenient.verdict("foo", Some("GPL-3.0"), false), Verdict::Deny);
+    }
+}
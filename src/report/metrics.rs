@@ -0,0 +1,211 @@
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
+    io::{self, Write},
+};
+
+use petgraph::{
+    algo::toposort,
+    stable_graph::NodeIndex,
+    visit::{Dfs, EdgeFiltered, EdgeRef, Reversed, Walker},
+    Direction,
+};
+use serde::Serialize;
+
+use crate::graph::DepGraph;
+
+/// Structural metrics of a package, to judge how load-bearing it is
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub(crate) struct NodeMetrics {
+    /// Number of packages that depend on this one directly
+    pub in_degree: u32,
+    /// Number of direct dependencies
+    pub out_degree: u32,
+    /// Length of the shortest chain from a workspace member, 0 for workspace members
+    pub depth: Option<u32>,
+    /// Number of distinct chains from the workspace members to this package
+    pub paths: u64,
+    /// Fraction of the shortest chains between two other packages that pass through this one
+    pub betweenness: f64,
+    /// Number of packages that depend on this one directly or transitively
+    pub reverse_deps: u32,
+}
+
+/// Metrics of a single package, as listed in the summary
+#[derive(Debug, Clone, Serialize)]
+pub struct PackageMetrics {
+    pub id: String,
+    #[serde(flatten)]
+    pub metrics: NodeMetrics,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricsSummary {
+    pub packages: usize,
+    pub dependencies: usize,
+    /// Length of the longest of the shortest chains from a workspace member
+    pub max_depth: u32,
+    /// All packages except for workspace members, most load-bearing first
+    pub ranking: Vec<PackageMetrics>,
+}
+
+/// Computes the metrics of every package in the graph
+pub(crate) fn set_metrics(graph: &mut DepGraph) {
+    let depth = depths(graph);
+    let paths = path_counts(graph);
+    let betweenness = betweenness(graph);
+
+    for idx in graph.node_indices().collect::<Vec<_>>() {
+        let neighbors = |dir| graph.neighbors_directed(idx, dir).collect::<HashSet<_>>().len();
+        // The package itself is not a reverse dependency of its own
+        let reverse_deps = Dfs::new(Reversed(&*graph), idx).iter(Reversed(&*graph)).count() - 1;
+        graph[idx].metrics = NodeMetrics {
+            in_degree: neighbors(Direction::Incoming) as u32,
+            out_degree: neighbors(Direction::Outgoing) as u32,
+            depth: depth.get(&idx).copied(),
+            paths: paths.get(&idx).copied().unwrap_or(0),
+            betweenness: betweenness.get(&idx).copied().unwrap_or(0.0),
+            reverse_deps: reverse_deps as u32,
+        };
+    }
+}
+
+/// Breadth-first search from all workspace members at once
+fn depths(graph: &DepGraph) -> HashMap<NodeIndex<u16>, u32> {
+    let mut depth = HashMap::new();
+    let mut queue = VecDeque::new();
+    for idx in graph.node_indices().filter(|&idx| graph[idx].is_ws_member) {
+        depth.insert(idx, 0);
+        queue.push_back(idx);
+    }
+    while let Some(idx) = queue.pop_front() {
+        let next = depth[&idx] + 1;
+        for dep in graph.neighbors_directed(idx, Direction::Outgoing) {
+            if let Entry::Vacant(entry) = depth.entry(dep) {
+                entry.insert(next);
+                queue.push_back(dep);
+            }
+        }
+    }
+    depth
+}
+
+/// Counts the chains from the workspace members to every package in topological order, instead
+/// of enumerating them. Dependencies that are part of a cycle are left out to make this possible.
+fn path_counts(graph: &DepGraph) -> HashMap<NodeIndex<u16>, u64> {
+    let acyclic = EdgeFiltered::from_fn(graph, |edge| !edge.weight().in_cycle);
+    let Ok(order) = toposort(&acyclic, None) else {
+        return HashMap::new();
+    };
+
+    let mut paths = HashMap::new();
+    for idx in order {
+        let mut count = u64::from(graph[idx].is_ws_member);
+        let dependents: HashSet<_> = graph
+            .edges_directed(idx, Direction::Incoming)
+            .filter(|edge| !edge.weight().in_cycle)
+            .map(|edge| edge.source())
+            .collect();
+        for dependent in dependents {
+            count = count.saturating_add(paths[&dependent]);
+        }
+        paths.insert(idx, count);
+    }
+    paths
+}
+
+/// Brandes' algorithm for betweenness centrality on the unweighted, directed graph, normalized by
+/// the number of ordered pairs of other packages
+fn betweenness(graph: &DepGraph) -> HashMap<NodeIndex<u16>, f64> {
+    let nodes: Vec<_> = graph.node_indices().collect();
+    let mut centrality: HashMap<_, _> = nodes.iter().map(|&idx| (idx, 0.0)).collect();
+
+    for &source in &nodes {
+        let mut stack = Vec::new();
+        let mut predecessors = HashMap::<_, Vec<_>>::new();
+        let mut sigma = HashMap::from([(source, 1.0)]);
+        let mut distance = HashMap::from([(source, 0)]);
+        let mut queue = VecDeque::from([source]);
+        while let Some(v) = queue.pop_front() {
+            stack.push(v);
+            let neighbors: HashSet<_> = graph.neighbors_directed(v, Direction::Outgoing).collect();
+            for w in neighbors {
+                if !distance.contains_key(&w) {
+                    distance.insert(w, distance[&v] + 1);
+                    queue.push_back(w);
+                }
+                if distance[&w] == distance[&v] + 1 {
+                    *sigma.entry(w).or_insert(0.0) += sigma[&v];
+                    predecessors.entry(w).or_default().push(v);
+                }
+            }
+        }
+
+        let mut delta = HashMap::<_, f64>::new();
+        while let Some(w) = stack.pop() {
+            let delta_w = delta.get(&w).copied().unwrap_or(0.0);
+            for &v in predecessors.get(&w).into_iter().flatten() {
+                *delta.entry(v).or_default() += sigma[&v] / sigma[&w] * (1.0 + delta_w);
+            }
+            if w != source {
+                *centrality.get_mut(&w).unwrap() += delta_w;
+            }
+        }
+    }
+
+    let n = nodes.len() as f64;
+    if n > 2.0 {
+        for value in centrality.values_mut() {
+            *value /= (n - 1.0) * (n - 2.0);
+        }
+    }
+    centrality
+}
+
+pub(crate) fn metrics(graph: &DepGraph) -> MetricsSummary {
+    let mut ranking: Vec<_> = graph
+        .node_weights()
+        .filter(|pkg| !pkg.is_ws_member)
+        .map(|pkg| PackageMetrics { id: pkg.id.clone(), metrics: pkg.metrics })
+        .collect();
+    ranking.sort_by(|a, b| {
+        let (a, b) = (&a.metrics, &b.metrics);
+        b.betweenness
+            .total_cmp(&a.betweenness)
+            .then(b.reverse_deps.cmp(&a.reverse_deps))
+            .then(b.paths.cmp(&a.paths))
+    });
+
+    MetricsSummary {
+        packages: graph.node_count(),
+        dependencies: graph.edge_count(),
+        max_depth: graph.node_weights().filter_map(|pkg| pkg.metrics.depth).max().unwrap_or(0),
+        ranking,
+    }
+}
+
+pub(crate) fn print(out: &mut dyn Write, graph: &DepGraph) -> io::Result<()> {
+    let summary = metrics(graph);
+    writeln!(
+        out,
+        "{} packages, {} dependencies, max depth {}",
+        summary.packages, summary.dependencies, summary.max_depth
+    )?;
+    writeln!(out)?;
+
+    let width = summary.ranking.iter().map(|pkg| pkg.id.len()).max().unwrap_or(0);
+    writeln!(
+        out,
+        "{:width$}  {:>11}  {:>8}  {:>12}  {:>6}  {:>4}  {:>5}",
+        "package", "betweenness", "rev deps", "paths", "depth", "in", "out"
+    )?;
+    for pkg in &summary.ranking {
+        let m = &pkg.metrics;
+        let depth = m.depth.map_or_else(|| "-".to_owned(), |d| d.to_string());
+        writeln!(
+            out,
+            "{:width$}  {:>11.4}  {:>8}  {:>12}  {depth:>6}  {:>4}  {:>5}",
+            pkg.id, m.betweenness, m.reverse_deps, m.paths, m.in_degree, m.out_degree
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use std::io::{self, Write};
+
+use cargo_metadata::semver::{Comparator, Op, Version, VersionReq};
+use serde::Serialize;
+
+use crate::{
+    graph::{DepGraph, DepMap},
+    registry::{RegistryCache, Release},
+};
+
+/// How the locked version of a package compares to the releases in the registry index
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct RegistryStatus {
+    /// Newest release that is semver compatible with the locked version, i.e. one that
+    /// `cargo update` could pick
+    pub latest_compatible: Option<Version>,
+    pub latest: Option<Version>,
+    /// Whether the locked version was yanked
+    pub yanked: bool,
+}
+
+impl RegistryStatus {
+    fn new(version: &Version, releases: &[Release]) -> Self {
+        let compatible = VersionReq {
+            comparators: vec![Comparator {
+                op: Op::Caret,
+                major: version.major,
+                minor: Some(version.minor),
+                patch: Some(version.patch),
+                pre: version.pre.clone(),
+            }],
+        };
+        // Pre-releases only count if the locked version is one as well
+        let candidates = releases
+            .iter()
+            .filter(|r| !r.yanked && (r.version.pre.is_empty() || !version.pre.is_empty()))
+            .map(|r| &r.version);
+
+        Self {
+            latest_compatible: candidates.clone().filter(|v| compatible.matches(v)).max().cloned(),
+            latest: candidates.max().cloned(),
+            yanked: releases.iter().any(|r| r.version == *version && r.yanked),
+        }
+    }
+
+    pub fn is_outdated(&self, version: &Version) -> bool {
+        self.yanked || self.latest.as_ref().is_some_and(|latest| latest > version)
+    }
+}
+
+/// Looks up every registry package in the index cache
+pub(crate) fn set_registry_status(graph: &mut DepGraph, depmap: &DepMap) {
+    let cache = RegistryCache::new();
+    for pkg in graph.node_weights_mut() {
+        // Feature nodes would only repeat their package
+        if pkg.feature.is_some() {
+            continue;
+        }
+        let Some(source) = depmap.get(&pkg.id).and_then(|meta| meta.source.as_ref()) else {
+            continue;
+        };
+        if let Some(releases) = cache.releases(source, &pkg.name) {
+            pkg.registry = Some(RegistryStatus::new(&pkg.version, &releases));
+        }
+    }
+}
+
+/// A package that is yanked or has newer releases
+#[derive(Debug, Clone, Serialize)]
+pub struct OutdatedPackage {
+    pub id: String,
+    pub name: String,
+    pub version: Version,
+    #[serde(flatten)]
+    pub status: RegistryStatus,
+}
+
+/// Lists the outdated packages, yanked ones first
+pub(crate) fn outdated(graph: &DepGraph) -> Vec<OutdatedPackage> {
+    let mut res: Vec<_> = graph
+        .node_weights()
+        .filter_map(|pkg| {
+            let status = pkg.registry.as_ref()?;
+            status.is_outdated(&pkg.version).then(|| OutdatedPackage {
+                id: pkg.id.clone(),
+                name: pkg.name.clone(),
+                version: pkg.version.clone(),
+                status: status.clone(),
+            })
+        })
+        .collect();
+    res.sort_by(|a, b| {
+        b.status
+            .yanked
+            .cmp(&a.status.yanked)
+            .then(a.name.cmp(&b.name))
+            .then(a.version.cmp(&b.version))
+    });
+    res
+}
+
+pub(crate) fn print(out: &mut dyn Write, graph: &DepGraph) -> io::Result<()> {
+    let outdated = outdated(graph);
+    if outdated.is_empty() {
+        return writeln!(out, "All packages are up to date with the cached registry index");
+    }
+
+    let width = outdated.iter().map(|pkg| pkg.name.len()).max().unwrap_or(0).max(4);
+    let version = |v: Option<&Version>| v.map_or_else(|| "-".to_owned(), Version::to_string);
+    writeln!(out, "{:width$}  {:12}  {:12}  latest", "name", "locked", "compatible")?;
+    for pkg in &outdated {
+        let compatible = pkg.status.latest_compatible.as_ref().filter(|&v| *v > pkg.version);
+        let line = format!(
+            "{:width$}  {:12}  {:12}  {:12}  {}",
+            pkg.name,
+            pkg.version.to_string(),
+            version(compatible),
+            version(pkg.status.latest.as_ref()),
+            if pkg.status.yanked { "yanked" } else { "" },
+        );
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
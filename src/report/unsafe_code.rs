@@ -0,0 +1,265 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    ops::AddAssign,
+    path::Path,
+};
+
+use cargo_metadata::{Package as MetaPackage, TargetKind};
+use petgraph::visit::Dfs;
+use serde::Serialize;
+use syn::{
+    visit::{self, Visit},
+    Attribute, ExprUnsafe, ImplItemFn, ItemFn, ItemImpl, ItemTrait, TraitItemFn,
+};
+
+use super::why::paths;
+use crate::{
+    graph::{DepGraph, DepMap},
+    util::package_files,
+};
+
+/// Maximum number of dependency paths printed per package
+const MAX_PRINTED_PATHS: usize = 3;
+
+/// Directories of a package whose code is not part of what dependents build
+const IGNORED_DIRS: &[&str] = &["tests", "benches", "examples"];
+
+/// Uses of `unsafe` in the source code of one or more packages.
+///
+/// Code that is generated by macros is not visible to the scanner, so `unsafe` in macro bodies
+/// is not counted.
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub(crate) struct UnsafeCounts {
+    pub blocks: u64,
+    pub functions: u64,
+    pub impls: u64,
+    pub traits: u64,
+    /// Source files that could not be parsed, so they may contain uncounted `unsafe`
+    pub unparsed_files: u64,
+}
+
+impl UnsafeCounts {
+    pub fn items(&self) -> u64 {
+        self.blocks + self.functions + self.impls + self.traits
+    }
+}
+
+impl AddAssign for UnsafeCounts {
+    fn add_assign(&mut self, rhs: Self) {
+        self.blocks += rhs.blocks;
+        self.functions += rhs.functions;
+        self.impls += rhs.impls;
+        self.traits += rhs.traits;
+        self.unparsed_files += rhs.unparsed_files;
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub(crate) struct PackageUnsafe {
+    /// The package on its own
+    pub own: UnsafeCounts,
+    /// The package along with all of its transitive dependencies
+    pub total: UnsafeCounts,
+    /// Whether the crate roots of all targets have `#![forbid(unsafe_code)]`
+    pub forbids_unsafe: bool,
+}
+
+impl Visit<'_> for UnsafeCounts {
+    fn visit_expr_unsafe(&mut self, node: &ExprUnsafe) {
+        self.blocks += 1;
+        visit::visit_expr_unsafe(self, node);
+    }
+
+    fn visit_item_fn(&mut self, node: &ItemFn) {
+        self.functions += u64::from(node.sig.unsafety.is_some());
+        visit::visit_item_fn(self, node);
+    }
+
+    fn visit_impl_item_fn(&mut self, node: &ImplItemFn) {
+        self.functions += u64::from(node.sig.unsafety.is_some());
+        visit::visit_impl_item_fn(self, node);
+    }
+
+    fn visit_trait_item_fn(&mut self, node: &TraitItemFn) {
+        self.functions += u64::from(node.sig.unsafety.is_some());
+        visit::visit_trait_item_fn(self, node);
+    }
+
+    fn visit_item_impl(&mut self, node: &ItemImpl) {
+        self.impls += u64::from(node.unsafety.is_some());
+        visit::visit_item_impl(self, node);
+    }
+
+    fn visit_item_trait(&mut self, node: &ItemTrait) {
+        self.traits += u64::from(node.unsafety.is_some());
+        visit::visit_item_trait(self, node);
+    }
+}
+
+/// Scans the sources of every package for `unsafe` and sums the results up over its dependencies
+pub(crate) fn set_unsafe_counts(graph: &mut DepGraph, depmap: &DepMap) {
+    let mut own = HashMap::new();
+    for idx in graph.node_indices() {
+        let pkg = &graph[idx];
+        // Feature nodes share the sources of their package
+        if pkg.feature.is_some() {
+            continue;
+        }
+        let Some(meta) = depmap.get(&pkg.id) else {
+            continue;
+        };
+        match scan(meta) {
+            Ok(res) => {
+                own.insert(idx, res);
+            }
+            Err(e) => eprintln!("Failed to scan the sources of {}: {e}", pkg.id),
+        }
+    }
+
+    for idx in graph.node_indices().collect::<Vec<_>>() {
+        let Some(&(counts, forbids_unsafe)) = own.get(&idx) else {
+            continue;
+        };
+        let mut total = UnsafeCounts::default();
+        let mut dfs = Dfs::new(&*graph, idx);
+        while let Some(dep) = dfs.next(&*graph) {
+            total += own.get(&dep).map(|&(counts, _)| counts).unwrap_or_default();
+        }
+        graph[idx].unsafe_code = Some(PackageUnsafe { own: counts, total, forbids_unsafe });
+    }
+}
+
+fn scan(meta: &MetaPackage) -> io::Result<(UnsafeCounts, bool)> {
+    let dir = meta.manifest_path.parent().unwrap().as_std_path();
+    let mut counts = UnsafeCounts::default();
+    for path in package_files(dir)? {
+        let ignored =
+            path.strip_prefix(dir).ok().and_then(|p| p.components().next()).is_some_and(|first| {
+                IGNORED_DIRS.iter().any(|ignored| first.as_os_str() == *ignored)
+            });
+        if ignored || path.extension().is_none_or(|ext| ext != "rs") {
+            continue;
+        }
+        match parse(&path) {
+            Some(file) => counts.visit_file(&file),
+            None => counts.unparsed_files += 1,
+        }
+    }
+
+    let roots: Vec<_> = meta
+        .targets
+        .iter()
+        .filter(|target| {
+            target.kind.iter().any(|kind| {
+                matches!(kind, TargetKind::Lib | TargetKind::ProcMacro | TargetKind::Bin)
+            })
+        })
+        .map(|target| target.src_path.as_std_path())
+        .collect();
+    let forbids_unsafe = !roots.is_empty()
+        && roots.iter().all(|root| parse(root).is_some_and(|file| forbids_unsafe(&file.attrs)));
+    Ok((counts, forbids_unsafe))
+}
+
+fn parse(path: &Path) -> Option<syn::File> {
+    syn::parse_file(&fs::read_to_string(path).ok()?).ok()
+}
+
+fn forbids_unsafe(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        if !attr.path().is_ident("forbid") {
+            return false;
+        }
+        let mut found = false;
+        let _ = attr.parse_nested_meta(|meta| {
+            found |= meta.path.is_ident("unsafe_code");
+            Ok(())
+        });
+        found
+    })
+}
+
+/// A workspace member along with the `unsafe` it pulls in
+#[derive(Debug, Clone, Serialize)]
+pub struct MemberUnsafe {
+    pub id: String,
+    #[serde(flatten)]
+    pub counts: PackageUnsafe,
+}
+
+/// A dependency that uses `unsafe` itself
+#[derive(Debug, Clone, Serialize)]
+pub struct UnsafeDependency {
+    pub id: String,
+    #[serde(flatten)]
+    pub counts: PackageUnsafe,
+    /// Chains of package ids from the workspace members to the package, shortest first
+    pub paths: Vec<Vec<String>>,
+    /// Whether there are more chains than were collected
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UnsafeReport {
+    pub members: Vec<MemberUnsafe>,
+    /// Dependencies with `unsafe` of their own, most uses first
+    pub dependencies: Vec<UnsafeDependency>,
+}
+
+pub(crate) fn unsafe_report(graph: &DepGraph) -> UnsafeReport {
+    let mut members = Vec::new();
+    let mut dependencies = Vec::new();
+    for idx in graph.node_indices() {
+        let pkg = &graph[idx];
+        let Some(counts) = pkg.unsafe_code else {
+            continue;
+        };
+        if pkg.is_ws_member {
+            members.push(MemberUnsafe { id: pkg.id.clone(), counts });
+        } else if counts.own.items() > 0 {
+            let (paths, truncated) = paths(graph, idx);
+            dependencies.push(UnsafeDependency { id: pkg.id.clone(), counts, paths, truncated });
+        }
+    }
+    members.sort_by(|a, b| a.id.cmp(&b.id));
+    dependencies
+        .sort_by(|a, b| b.counts.own.items().cmp(&a.counts.own.items()).then(a.id.cmp(&b.id)));
+    UnsafeReport { members, dependencies }
+}
+
+fn summary(counts: &UnsafeCounts) -> String {
+    let mut res = format!(
+        "{} blocks, {} functions, {} impls, {} traits",
+        counts.blocks, counts.functions, counts.impls, counts.traits
+    );
+    if counts.unparsed_files > 0 {
+        res.push_str(&format!(", {} files not parsed", counts.unparsed_files));
+    }
+    res
+}
+
+pub(crate) fn print(out: &mut dyn Write, graph: &DepGraph) -> io::Result<()> {
+    let report = unsafe_report(graph);
+    for member in &report.members {
+        let forbids = if member.counts.forbids_unsafe { ", forbids unsafe code" } else { "" };
+        writeln!(out, "{}{forbids}", member.id)?;
+        writeln!(out, "  own: {}", summary(&member.counts.own))?;
+        writeln!(out, "  with dependencies: {}", summary(&member.counts.total))?;
+    }
+
+    for dep in &report.dependencies {
+        writeln!(out)?;
+        writeln!(out, "{}: {}", dep.id, summary(&dep.counts.own))?;
+        for path in dep.paths.iter().take(MAX_PRINTED_PATHS) {
+            writeln!(out, "  via {}", path.join(" -> "))?;
+        }
+        let more = dep.paths.len().saturating_sub(MAX_PRINTED_PATHS);
+        if more > 0 || dep.truncated {
+            let plus = if dep.truncated { "+" } else { "" };
+            writeln!(out, "  and {more}{plus} more paths")?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+use petgraph::{stable_graph::NodeIndex, Direction};
+use serde::Serialize;
+
+use crate::{
+    dep_info::DepInfoInner,
+    graph::{find_packages, DepGraph},
+    util::edge_labels,
+};
+
+/// Upper bound for the number of chains collected per package, the number of simple paths can
+/// grow exponentially with the size of the graph.
+const MAX_CHAINS: usize = 1000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WhyInfo {
+    pub id: String,
+    /// Number of chains found, at most `MAX_CHAINS`
+    pub total: usize,
+    /// Whether there are more chains than were collected
+    pub truncated: bool,
+    /// Chains grouped by their length, shortest first
+    pub groups: Vec<WhyGroup>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WhyGroup {
+    pub length: usize,
+    pub chains: Vec<Vec<WhyStep>>,
+}
+
+/// A single dependency in a chain, along with all the edges between the two packages
+#[derive(Debug, Clone, Serialize)]
+pub struct WhyStep {
+    pub source: String,
+    pub target: String,
+    pub edges: Vec<DepInfoInner>,
+}
+
+/// Collects the dependency chains that lead from workspace members to the package at `idx`.
+///
+/// Chains start at the nearest workspace member, i.e. they never pass through another one.
+pub(crate) fn why(graph: &DepGraph, idx: NodeIndex<u16>) -> WhyInfo {
+    let mut chains = Vec::new();
+    let mut truncated = false;
+    // Walk the graph backwards from the package, so that every path explored ends at it
+    let mut path = vec![idx];
+    collect_chains(graph, &mut path, &mut chains, &mut truncated);
+
+    let mut groups = BTreeMap::<usize, Vec<Vec<WhyStep>>>::new();
+    let total = chains.len();
+    for chain in chains {
+        let steps: Vec<_> = chain
+            .windows(2)
+            .map(|w| WhyStep {
+                source: graph[w[0]].id.clone(),
+                target: graph[w[1]].id.clone(),
+                edges: graph.edges_connecting(w[0], w[1]).map(|e| e.weight().inner).collect(),
+            })
+            .collect();
+        groups.entry(steps.len()).or_default().push(steps);
+    }
+
+    WhyInfo {
+        id: graph[idx].id.clone(),
+        total,
+        truncated,
+        groups: groups
+            .into_iter()
+            .map(|(length, mut chains)| {
+                chains.sort_by(|a, b| {
+                    let ids =
+                        |c: &Vec<WhyStep>| c.iter().map(|s| s.source.clone()).collect::<Vec<_>>();
+                    ids(a).cmp(&ids(b))
+                });
+                WhyGroup { length, chains }
+            })
+            .collect(),
+    }
+}
+
+fn collect_chains(
+    graph: &DepGraph,
+    path: &mut Vec<NodeIndex<u16>>,
+    chains: &mut Vec<Vec<NodeIndex<u16>>>,
+    truncated: &mut bool,
+) {
+    let current = *path.last().unwrap();
+    if graph[current].is_ws_member && path.len() > 1 {
+        chains.push(path.iter().rev().copied().collect());
+        return;
+    }
+
+    let mut parents: Vec<_> = graph.neighbors_directed(current, Direction::Incoming).collect();
+    parents.sort_unstable();
+    parents.dedup();
+    for parent in parents {
+        if chains.len() >= MAX_CHAINS {
+            *truncated = true;
+            return;
+        }
+        if path.contains(&parent) {
+            continue;
+        }
+        path.push(parent);
+        collect_chains(graph, path, chains, truncated);
+        path.pop();
+    }
+}
+
+/// Chains of package ids from the workspace members to the package at `idx`, shortest first,
+/// along with whether there are more chains than were collected
+pub(crate) fn paths(graph: &DepGraph, idx: NodeIndex<u16>) -> (Vec<Vec<String>>, bool) {
+    let info = why(graph, idx);
+    let paths = info
+        .groups
+        .into_iter()
+        .flat_map(|group| group.chains)
+        .map(|chain| {
+            let mut ids: Vec<_> = chain.iter().map(|step| step.source.clone()).collect();
+            ids.push(info.id.clone());
+            ids
+        })
+        .collect();
+    (paths, info.truncated)
+}
+
+/// Prints why the packages matching `spec` are part of the graph
+pub(crate) fn print(out: &mut dyn Write, graph: &DepGraph, spec: &str) -> anyhow::Result<()> {
+    let packages = find_packages(graph, spec);
+    if packages.is_empty() {
+        anyhow::bail!("package `{spec}` is not part of the dependency graph");
+    }
+
+    for (i, idx) in packages.into_iter().enumerate() {
+        if i > 0 {
+            writeln!(out)?;
+        }
+        let info = why(graph, idx);
+        if graph[idx].is_ws_member && info.total == 0 {
+            writeln!(out, "{} is a workspace member", info.id)?;
+            continue;
+        }
+        let more = if info.truncated { "more than " } else { "" };
+        writeln!(out, "{} is pulled in by {more}{} dependency chains:", info.id, info.total)?;
+        for group in &info.groups {
+            writeln!(out)?;
+            writeln!(out, "Length {}:", group.length)?;
+            for chain in &group.chains {
+                print_chain(out, chain)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_chain(out: &mut dyn Write, chain: &[WhyStep]) -> io::Result<()> {
+    write!(out, "  {}", chain[0].source)?;
+    for step in chain {
+        let labels = edge_labels(&step.edges);
+        if labels.is_empty() {
+            write!(out, " -> {}", step.target)?;
+        } else {
+            write!(out, " -({})-> {}", labels.join(", "), step.target)?;
+        }
+    }
+    writeln!(out)
+}
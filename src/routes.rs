@@ -1,5 +1,16 @@
 use crate::{
-    dto::{DepGraphEdges, DepGraphInfo, DepGraphNodes},
+    dto::{DepGraphEdges, DepGraphInfo, DepGraphNodes, PackageInfo},
+    graph::find_packages,
+    report::{
+        advisories::{advisories, AffectedPackage},
+        cycles::{cycles, CyclesInfo},
+        duplicates::{duplicates, Duplicate},
+        licenses::{licenses, LicenseGroup},
+        metrics::{metrics, MetricsSummary},
+        outdated::{outdated, OutdatedPackage},
+        unsafe_code::{unsafe_report, UnsafeReport},
+        why::{why, WhyInfo},
+    },
     AppState,
 };
 use axum::{
@@ -7,13 +18,15 @@ use axum::{
     http::StatusCode,
     Json,
 };
-use cargo_metadata::Package;
 
 pub async fn handler_open(
     State(state): State<AppState>,
     Path((id, field)): Path<(String, String)>,
 ) -> StatusCode {
-    let Some(pkg) = state.depmap.get(&id) else {
+    let Some(pkg) = state.graph.node_weights().find(|pkg| pkg.id == id) else {
+        return StatusCode::NOT_FOUND;
+    };
+    let Some(pkg) = state.depmap.get(pkg.package_id()) else {
         return StatusCode::NOT_FOUND;
     };
     let Some(basedir) = pkg.manifest_path.parent() else {
@@ -40,9 +53,11 @@ pub async fn handler_open(
 pub async fn handler_crate_info(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> Result<Json<Package>, StatusCode> {
-    if let Some(pkg) = state.depmap.get(&id) {
-        Ok(Json(pkg.clone()))
+) -> Result<Json<PackageInfo>, StatusCode> {
+    let pkg = state.graph.node_weights().find(|pkg| pkg.id == id);
+    let meta = pkg.and_then(|pkg| state.depmap.get(pkg.package_id()));
+    if let (Some(meta), Some(pkg)) = (meta, pkg) {
+        Ok(Json(PackageInfo::new(meta, pkg)))
     } else {
         Err(StatusCode::NOT_FOUND)
     }
@@ -51,10 +66,7 @@ pub async fn handler_crate_info(
 pub async fn handler_graph(
     State(state): State<AppState>,
 ) -> Result<Json<DepGraphInfo>, StatusCode> {
-    Ok(Json(DepGraphInfo {
-        nodes: state.graph.node_weights().cloned().map(Into::into).collect(),
-        edges: state.graph.edge_weights().cloned().map(Into::into).collect(),
-    }))
+    Ok(Json(DepGraphInfo::from(&*state.graph)))
 }
 
 pub async fn handler_nodes(
@@ -72,3 +84,53 @@ pub async fn handler_edges(
         values: state.graph.edge_weights().cloned().map(Into::into).collect(),
     }))
 }
+
+pub async fn handler_why(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<WhyInfo>, StatusCode> {
+    let Some(&idx) = find_packages(&state.graph, &id).first() else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    Ok(Json(why(&state.graph, idx)))
+}
+
+pub async fn handler_duplicates(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Duplicate>>, StatusCode> {
+    Ok(Json(duplicates(&state.graph, &state.depmap)))
+}
+
+pub async fn handler_cycles(State(state): State<AppState>) -> Result<Json<CyclesInfo>, StatusCode> {
+    Ok(Json(cycles(&state.graph)))
+}
+
+pub async fn handler_metrics(
+    State(state): State<AppState>,
+) -> Result<Json<MetricsSummary>, StatusCode> {
+    Ok(Json(metrics(&state.graph)))
+}
+
+pub async fn handler_advisories(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<AffectedPackage>>, StatusCode> {
+    Ok(Json(advisories(&state.graph)))
+}
+
+pub async fn handler_outdated(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<OutdatedPackage>>, StatusCode> {
+    Ok(Json(outdated(&state.graph)))
+}
+
+pub async fn handler_unsafe(
+    State(state): State<AppState>,
+) -> Result<Json<UnsafeReport>, StatusCode> {
+    Ok(Json(unsafe_report(&state.graph)))
+}
+
+pub async fn handler_licenses(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<LicenseGroup>>, StatusCode> {
+    Ok(Json(licenses(&state.graph, &state.depmap)))
+}
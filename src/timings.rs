@@ -0,0 +1,199 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use anyhow::{bail, Context};
+use petgraph::{stable_graph::NodeIndex, Direction};
+use serde::Deserialize;
+
+use crate::graph::{DepGraph, DepMap};
+
+/// How long it took to compile one unit, i.e. a target of a package in some mode like running its
+/// build script
+#[derive(Clone, Debug)]
+pub(crate) struct UnitTiming {
+    /// Package id as printed by cargo, or `name version` for the HTML report
+    pub package_id: String,
+    /// Seconds
+    pub duration: f64,
+}
+
+/// Entry of `UNIT_DATA` in the HTML report written by `cargo build --timings`
+#[derive(Deserialize)]
+struct HtmlUnit {
+    name: String,
+    version: String,
+    duration: f64,
+}
+
+/// `timing-info` message of `cargo build --timings=json` or `--message-format=json`
+#[derive(Deserialize)]
+struct JsonUnit {
+    reason: String,
+    #[serde(default)]
+    package_id: String,
+    #[serde(default)]
+    duration: f64,
+}
+
+/// Reads the unit timings from an HTML report or from cargo's JSON messages
+pub(crate) fn load(path: &Path) -> anyhow::Result<Vec<UnitTiming>> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read timings {}", path.display()))?;
+    let units = if let Some((_, data)) = text.split_once("const UNIT_DATA = ") {
+        // Only the first value is of interest, the rest of the script follows it
+        let units = serde_json::Deserializer::from_str(data)
+            .into_iter::<Vec<HtmlUnit>>()
+            .next()
+            .context("Missing unit data")?
+            .with_context(|| format!("Failed to parse timings {}", path.display()))?;
+        units
+            .into_iter()
+            .map(|unit| UnitTiming {
+                package_id: format!("{} {}", unit.name, unit.version),
+                duration: unit.duration,
+            })
+            .collect()
+    } else {
+        // Other messages and lines that are not JSON at all are interleaved
+        text.lines()
+            .filter_map(|line| serde_json::from_str::<JsonUnit>(line).ok())
+            .filter(|unit| unit.reason == "timing-info")
+            .map(|unit| UnitTiming { package_id: unit.package_id, duration: unit.duration })
+            .collect::<Vec<_>>()
+    };
+    if units.is_empty() {
+        bail!("{} contains no timing information", path.display());
+    }
+    Ok(units)
+}
+
+/// Adds up the durations of the units of every package, and marks the packages on the critical
+/// path, i.e. the chain of dependencies that takes the longest to compile one after the other
+pub(crate) fn set_build_times(graph: &mut DepGraph, depmap: &DepMap, units: &[UnitTiming]) {
+    let ids: HashMap<_, _> = graph
+        .node_weights()
+        .filter(|pkg| pkg.feature.is_none())
+        .filter_map(|pkg| Some((depmap.get(&pkg.id)?.id.repr.clone(), pkg.id.clone())))
+        .collect();
+    let mut durations = HashMap::<String, f64>::new();
+    for unit in units {
+        let id = match ids.get(unit.package_id.as_str()) {
+            Some(id) => id.clone(),
+            None => graph_id(&unit.package_id),
+        };
+        *durations.entry(id).or_default() += unit.duration;
+    }
+
+    for pkg in graph.node_weights_mut() {
+        if pkg.feature.is_none() {
+            pkg.build_time = durations.get(&pkg.id).copied();
+        }
+    }
+
+    let mut finish = HashMap::new();
+    for idx in graph.node_indices() {
+        finish_time(graph, idx, &mut finish, &mut Vec::new());
+    }
+    let Some(mut idx) = graph
+        .node_indices()
+        .filter(|&idx| graph[idx].is_ws_member)
+        .max_by(|a, b| finish[a].total_cmp(&finish[b]))
+    else {
+        return;
+    };
+    let mut path = HashSet::new();
+    loop {
+        path.insert(idx);
+        if graph[idx].feature.is_none() {
+            graph[idx].on_critical_path = true;
+        }
+        let Some(next) = graph
+            .neighbors_directed(idx, Direction::Outgoing)
+            .filter(|dep| finish[dep] > 0.0 && !path.contains(dep))
+            .max_by(|a, b| finish[a].total_cmp(&finish[b]))
+        else {
+            break;
+        };
+        idx = next;
+    }
+}
+
+/// Time after which `idx` is compiled, if everything that can be compiled in parallel is
+fn finish_time(
+    graph: &DepGraph,
+    idx: NodeIndex<u16>,
+    finish: &mut HashMap<NodeIndex<u16>, f64>,
+    stack: &mut Vec<NodeIndex<u16>>,
+) -> f64 {
+    if let Some(&time) = finish.get(&idx) {
+        return time;
+    }
+    stack.push(idx);
+    let mut start = 0.0_f64;
+    for dep in graph.neighbors_directed(idx, Direction::Outgoing) {
+        // Dev-dependencies can depend on the workspace member back, but those are built
+        // separately
+        if !stack.contains(&dep) {
+            start = start.max(finish_time(graph, dep, finish, stack));
+        }
+    }
+    stack.pop();
+    let time = start + graph[idx].build_time.unwrap_or(0.0);
+    finish.insert(idx, time);
+    time
+}
+
+/// Turns the package id of a cargo message into the id used in the graph.
+///
+/// Older versions of cargo print `name version (source)`, newer ones a package id spec like
+/// `registry+https://github.com/rust-lang/crates.io-index#name@version`, where the name is
+/// left out if it matches the last path segment.
+fn graph_id(package_id: &str) -> String {
+    if let Some((url, fragment)) = package_id.split_once('#') {
+        return match fragment.split_once('@') {
+            Some((name, version)) => format!("{name} {version}"),
+            None => {
+                let path = url.split('?').next().unwrap_or(url);
+                let name = path.trim_end_matches('/').rsplit('/').next().unwrap_or(path);
+                format!("{name} {fragment}")
+            }
+        };
+    }
+    package_id.split(' ').take(2).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graph_id_of_package_id_spec() {
+        assert_eq!(
+            graph_id("registry+https://github.com/rust-lang/crates.io-index#serde@1.0.219"),
+            "serde 1.0.219"
+        );
+        assert_eq!(
+            graph_id("git+https://github.com/rust-lang/cargo?branch=master#cargo-util@0.2.20"),
+            "cargo-util 0.2.20"
+        );
+    }
+
+    #[test]
+    fn graph_id_of_package_id_spec_without_name() {
+        assert_eq!(graph_id("path+file:///home/user/foo#0.1.0"), "foo 0.1.0");
+        assert_eq!(graph_id("path+file:///home/user/foo/#0.1.0"), "foo 0.1.0");
+        assert_eq!(graph_id("git+https://github.com/user/foo?rev=abc123#1.2.3"), "foo 1.2.3");
+    }
+
+    #[test]
+    fn graph_id_of_old_package_id() {
+        assert_eq!(
+            graph_id("serde 1.0.219 (registry+https://github.com/rust-lang/crates.io-index)"),
+            "serde 1.0.219"
+        );
+        assert_eq!(graph_id("foo 0.1.0 (path+file:///home/user/foo)"), "foo 0.1.0");
+    }
+}
@@ -1,5 +1,7 @@
 use std::{
     collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicU16, Ordering},
         Arc,
@@ -8,7 +10,7 @@ use std::{
 
 use cargo_metadata::{Package as MetaPackage, TargetKind};
 
-use crate::graph::DepGraph;
+use crate::{dep_info::DepInfoInner, graph::DepGraph};
 
 pub(crate) fn set_name_stats(graph: &mut DepGraph) {
     let mut name_uses_map = HashMap::<String, Arc<AtomicU16>>::new();
@@ -28,3 +30,55 @@ pub(crate) fn is_proc_macro(pkg: &MetaPackage) -> bool {
 
     res
 }
+
+/// Short description of the (possibly parallel) edges leading to a package
+pub(crate) fn edge_labels(edges: &[DepInfoInner]) -> Vec<&'static str> {
+    let mut res = Vec::new();
+    if edges.iter().any(|e| e.is_dev) {
+        res.push("dev");
+    }
+    if edges.iter().any(|e| e.is_build) {
+        res.push("build");
+    }
+    // A dependency is only optional if all edges leading to it are
+    if !edges.is_empty() && edges.iter().all(|e| e.is_optional) {
+        if edges.iter().any(|e| e.is_optional_direct) {
+            res.push("optional");
+        } else {
+            res.push("transitively optional");
+        }
+    }
+    if !edges.is_empty() && edges.iter().all(|e| e.is_target_dep) {
+        res.push("target-specific");
+    }
+    res
+}
+
+/// Files of the package in `dir`, leaving out build output, hidden directories and nested
+/// packages
+pub(crate) fn package_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    collect_package_files(dir, &mut files)?;
+    Ok(files)
+}
+
+fn collect_package_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let path = entry.path();
+        if file_type.is_dir() {
+            let name = entry.file_name();
+            if name == "target"
+                || name.to_string_lossy().starts_with('.')
+                || path.join("Cargo.toml").is_file()
+            {
+                continue;
+            }
+            collect_package_files(&path, files)?;
+        } else if file_type.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(())
+}